@@ -0,0 +1,2 @@
+DROP INDEX download_cache_last_played;
+DROP TABLE download_cache;
@@ -0,0 +1,9 @@
+CREATE TABLE download_cache (
+    video_id VARCHAR(64) PRIMARY KEY,
+    file_path VARCHAR(1024) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    last_played TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    pinned BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX download_cache_last_played ON download_cache (last_played);
@@ -0,0 +1,2 @@
+DROP INDEX unique_media_per_extractor;
+DROP TABLE media_metadata;
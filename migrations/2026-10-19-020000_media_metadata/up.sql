@@ -0,0 +1,13 @@
+CREATE TABLE media_metadata (
+    id BIGSERIAL PRIMARY KEY,
+    extractor VARCHAR(64) NOT NULL,
+    video_id VARCHAR(64) NOT NULL,
+    url VARCHAR(1024) NOT NULL,
+    title VARCHAR(1024) NOT NULL,
+    uploader VARCHAR(256),
+    duration_secs DOUBLE PRECISION,
+    thumbnail_url VARCHAR(2048),
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX unique_media_per_extractor ON media_metadata (extractor, video_id);
@@ -0,0 +1,5 @@
+ALTER TABLE audio_ledger
+    DROP COLUMN codec,
+    DROP COLUMN sample_rate,
+    DROP COLUMN channels,
+    DROP COLUMN duration_secs;
@@ -0,0 +1,5 @@
+ALTER TABLE audio_ledger
+    ADD COLUMN codec VARCHAR(32),
+    ADD COLUMN sample_rate INTEGER,
+    ADD COLUMN channels SMALLINT,
+    ADD COLUMN duration_secs DOUBLE PRECISION;
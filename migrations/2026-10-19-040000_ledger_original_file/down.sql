@@ -0,0 +1 @@
+ALTER TABLE audio_ledger DROP COLUMN original_file_path;
@@ -0,0 +1 @@
+ALTER TABLE audio_ledger ADD COLUMN original_file_path VARCHAR(1024);
@@ -0,0 +1,8 @@
+-- Only reversible while no two sounds share a blob. Once deduplicated sounds point at the same file, putting the
+-- file_path constraint back fails; remove the extra names (or give them their own copy of the file) first.
+DROP INDEX audio_ledger_blob_hash;
+
+ALTER TABLE audio_ledger DROP COLUMN blob_hash;
+ALTER TABLE audio_ledger ADD CONSTRAINT audio_ledger_file_path_key UNIQUE (file_path);
+
+DROP TABLE audio_blobs;
@@ -0,0 +1,11 @@
+CREATE TABLE audio_blobs (
+    hash VARCHAR(64) PRIMARY KEY,
+    directory VARCHAR(1024) NOT NULL,
+    ref_count BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE audio_ledger DROP CONSTRAINT audio_ledger_file_path_key;
+ALTER TABLE audio_ledger ADD COLUMN blob_hash VARCHAR(64) REFERENCES audio_blobs (hash);
+
+CREATE INDEX audio_ledger_blob_hash ON audio_ledger (blob_hash);
@@ -0,0 +1,9 @@
+-- Only reversible while every link_or_name is unique. Once two uploaders have a sound with the same name (or added
+-- the same youtube link), putting the link_or_name constraint back fails; rename or remove the duplicates first.
+DROP INDEX audio_ledger_guild_name;
+DROP INDEX unique_sound_name_per_uploader;
+ALTER TABLE audio_ledger ADD CONSTRAINT audio_ledger_link_or_name_key UNIQUE (link_or_name);
+
+ALTER TABLE audio_ledger
+    DROP COLUMN visibility,
+    DROP COLUMN guild_id;
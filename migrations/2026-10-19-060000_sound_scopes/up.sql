@@ -0,0 +1,11 @@
+ALTER TABLE audio_ledger
+    ADD COLUMN guild_id NUMERIC(20),
+    ADD COLUMN visibility VARCHAR(16) NOT NULL DEFAULT 'guild' CHECK (visibility IN ('private', 'guild', 'public'));
+
+-- Nothing recorded which guild existing sounds were uploaded in. They were usable in any guild their uploader is in,
+-- which is what `guild` visibility without a `guild_id` keeps meaning.
+UPDATE audio_ledger SET guild_id = NULL, visibility = 'guild';
+
+ALTER TABLE audio_ledger DROP CONSTRAINT audio_ledger_link_or_name_key;
+CREATE UNIQUE INDEX unique_sound_name_per_uploader ON audio_ledger (uploader, link_or_name);
+CREATE INDEX audio_ledger_guild_name ON audio_ledger (guild_id, link_or_name);
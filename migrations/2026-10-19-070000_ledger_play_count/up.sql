@@ -0,0 +1 @@
+ALTER TABLE audio_ledger ADD COLUMN play_count BIGINT NOT NULL DEFAULT 0;
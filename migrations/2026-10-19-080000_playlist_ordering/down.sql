@@ -0,0 +1,10 @@
+DROP INDEX playlist_entries_position;
+
+ALTER TABLE playlist_entries
+    DROP CONSTRAINT playlist_entries_audio_fkey,
+    DROP CONSTRAINT playlist_entries_playlist_fkey,
+    ADD CONSTRAINT playlist_entries_playlist_fkey FOREIGN KEY (playlist) REFERENCES playlists,
+    ADD CONSTRAINT playlist_entries_audio_fkey FOREIGN KEY (audio) REFERENCES audio_ledger,
+    ALTER COLUMN audio DROP NOT NULL,
+    ALTER COLUMN playlist DROP NOT NULL,
+    DROP COLUMN position;
@@ -0,0 +1,21 @@
+-- Entries missing either side never pointed at anything playable.
+DELETE FROM playlist_entries WHERE playlist IS NULL OR audio IS NULL;
+
+ALTER TABLE playlist_entries ADD COLUMN position INTEGER;
+UPDATE playlist_entries SET position = ordered.position
+FROM (
+    SELECT id, (ROW_NUMBER() OVER (PARTITION BY playlist ORDER BY id) - 1)::INTEGER AS position
+    FROM playlist_entries
+) AS ordered
+WHERE playlist_entries.id = ordered.id;
+
+ALTER TABLE playlist_entries
+    ALTER COLUMN position SET NOT NULL,
+    ALTER COLUMN playlist SET NOT NULL,
+    ALTER COLUMN audio SET NOT NULL,
+    DROP CONSTRAINT playlist_entries_playlist_fkey,
+    DROP CONSTRAINT playlist_entries_audio_fkey,
+    ADD CONSTRAINT playlist_entries_playlist_fkey FOREIGN KEY (playlist) REFERENCES playlists ON DELETE CASCADE,
+    ADD CONSTRAINT playlist_entries_audio_fkey FOREIGN KEY (audio) REFERENCES audio_ledger ON DELETE CASCADE;
+
+CREATE INDEX playlist_entries_position ON playlist_entries (playlist, position);
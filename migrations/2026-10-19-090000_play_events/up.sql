@@ -0,0 +1,13 @@
+CREATE TABLE play_events (
+    id BIGSERIAL PRIMARY KEY,
+    guild_id NUMERIC(20) NOT NULL,
+    user_id NUMERIC(20) NOT NULL,
+    source VARCHAR(1024) NOT NULL,
+    title VARCHAR(1024) NOT NULL,
+    kind VARCHAR(16) NOT NULL CHECK (kind IN ('start', 'skip', 'complete')),
+    seconds_listened DOUBLE PRECISION NOT NULL DEFAULT 0,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX play_events_guild_time ON play_events (guild_id, occurred_at);
+CREATE INDEX play_events_user_time ON play_events (user_id, occurred_at);
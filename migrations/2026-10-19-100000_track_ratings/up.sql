@@ -0,0 +1,10 @@
+CREATE TABLE track_ratings (
+    id BIGSERIAL PRIMARY KEY,
+    discord_user NUMERIC(20, 0) NOT NULL,
+    audio BIGINT NOT NULL REFERENCES audio_ledger ON DELETE CASCADE,
+    rating SMALLINT NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    rated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX unique_track_rating_per_user ON track_ratings (discord_user, audio);
+CREATE INDEX track_ratings_audio ON track_ratings (audio);
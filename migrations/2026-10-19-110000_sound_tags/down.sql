@@ -0,0 +1,2 @@
+DROP INDEX audio_ledger_name_trigram;
+DROP TABLE audio_tags;
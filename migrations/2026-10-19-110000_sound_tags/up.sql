@@ -0,0 +1,11 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE TABLE audio_tags (
+    id BIGSERIAL PRIMARY KEY,
+    audio BIGINT NOT NULL REFERENCES audio_ledger ON DELETE CASCADE,
+    tag VARCHAR(32) NOT NULL
+);
+
+CREATE UNIQUE INDEX unique_tag_per_audio ON audio_tags (audio, tag);
+CREATE INDEX audio_tags_tag ON audio_tags (tag);
+CREATE INDEX audio_ledger_name_trigram ON audio_ledger USING GIN (link_or_name gin_trgm_ops);
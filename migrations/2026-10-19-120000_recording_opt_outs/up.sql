@@ -0,0 +1,4 @@
+CREATE TABLE recording_opt_outs (
+    discord_user NUMERIC(20, 0) PRIMARY KEY,
+    opted_out_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+CREATE TABLE guild_settings (
+    guild_id NUMERIC(20, 0) PRIMARY KEY,
+    clips_enabled BOOLEAN NOT NULL DEFAULT FALSE
+);
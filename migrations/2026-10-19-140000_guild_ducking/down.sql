@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    DROP COLUMN ducking_enabled,
+    DROP COLUMN ducking_volume;
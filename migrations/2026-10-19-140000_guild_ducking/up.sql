@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    ADD COLUMN ducking_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN ducking_volume SMALLINT NOT NULL DEFAULT 20 CHECK (ducking_volume BETWEEN 0 AND 100);
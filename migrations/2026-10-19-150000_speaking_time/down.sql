@@ -0,0 +1,2 @@
+DROP TABLE speaking_time;
+DROP TABLE voice_stats_opt_ins;
@@ -0,0 +1,14 @@
+CREATE TABLE voice_stats_opt_ins (
+    discord_user NUMERIC(20, 0) PRIMARY KEY,
+    opted_in_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Only totals, never any audio.
+CREATE TABLE speaking_time (
+    guild_id NUMERIC(20, 0) NOT NULL,
+    discord_user NUMERIC(20, 0) NOT NULL,
+    day DATE NOT NULL,
+    seconds_spoken DOUBLE PRECISION NOT NULL DEFAULT 0,
+    PRIMARY KEY (guild_id, discord_user, day)
+);
+CREATE INDEX speaking_time_discord_user ON speaking_time (discord_user, day);
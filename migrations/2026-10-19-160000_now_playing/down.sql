@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    DROP COLUMN now_playing_mode,
+    DROP COLUMN announce_channel;
@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    ADD COLUMN now_playing_mode VARCHAR(16) NOT NULL DEFAULT 'off',
+    ADD COLUMN announce_channel NUMERIC(20, 0);
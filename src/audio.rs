@@ -1,20 +1,304 @@
-use crate::async_trait;
+use std::{path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, time::Duration};
+
+use azel::{cmd::RequestError, discord::ExecutionContext, DatabaseConfiguration};
+use crate::{async_trait, cache::{self, CacheLease, CacheManager}, db::{self, stats::{self, NewPlayEvent, PlayEventKind}}, download::DownloadStatus, limits::{format_duration, shorten, LISTED_NAME_LENGTH}, now_playing::NowPlayingAnnouncer, playback::{self, PlaybackManager}};
+use serenity::all::{ChannelId, GuildId, Http, Mention, UserId};
+use tokio::sync::watch;
 use tracing as trc;
 
-use songbird::{EventContext, Event, EventHandler};
+use songbird::{input::{cached::Memory, Input}, tracks::{PlayError, PlayMode, Track, TrackHandle}, EventContext, Event, EventHandler, Songbird, TrackEvent};
+
+/// How long a failed stream waits on its download before giving up on retrying.
+const RETRY_DOWNLOAD_WAIT: Duration = Duration::from_secs(5 * 60);
+
+/// What we know about a queued track. Lives in the track's typemap so queue views don't need to hit the db.
+#[derive(Debug, Clone)]
+pub struct TrackInfo {
+    pub title: String,
+    /// Youtube url or sound name, as given to `/play`.
+    pub source: String,
+    pub duration_secs: Option<f64>,
+    pub thumbnail_url: Option<String>,
+    pub requester: UserId,
+    /// Ledger entry of an uploaded sound, so it can be found again without going by name.
+    pub sound_id: Option<i64>,
+}
+
+impl TrackInfo {
+    /// Title, linked to the source if it's a url. Meant for chat messages.
+    pub fn display(&self) -> String {
+        let title = shorten(self.title.as_str(), LISTED_NAME_LENGTH).replace('[', "\\[").replace(']', "\\]");
+        let title = if self.source.starts_with("https://") {
+            format!("[{title}](<{}>)", self.source)
+        } else {
+            format!("**{title}**")
+        };
+        match self.duration_secs {
+            Some(duration) => format!("{title} ({})", format_duration(duration as u64)),
+            None => title,
+        }
+    }
+}
+
+/// A track carrying its [`TrackInfo`]. Everything queued goes through here, which [`track_info`] relies on.
+pub fn new_track(input: Input, info: TrackInfo) -> Track {
+    Track::new_with_data(input, Arc::new(info))
+}
+
+pub fn track_info(handle: &TrackHandle) -> Arc<TrackInfo> {
+    handle.data::<TrackInfo>()
+}
+
+/// Loads a file fully into memory to play from.
+pub async fn cached_input(path: PathBuf) -> Result<Input, RequestError> {
+    // Going through `File` lets symphonia use the extension as a format hint.
+    let memory = Memory::new(songbird::input::File::new(path).into()).await
+        .map_err(|e| RequestError::Internal(format!("audio file failed to load {e:?}").into()))?;
+    Ok(Input::from(memory))
+}
+
+/// Another way to play a track, should it fail.
+pub enum TrackRetry {
+    /// It's being streamed while downloading, so the download can stand in once done.
+    FromDownload {
+        video_id: String,
+        download: watch::Receiver<DownloadStatus>,
+    },
+}
+
+/// Tells the channel a track was queued from when it fails, and tries getting it going again if there's a way to.
+/// Otherwise the queue just moves on to the next track.
+#[derive(Clone)]
+pub struct TrackErrorNotifier {
+    http: Arc<Http>,
+    channel: ChannelId,
+    guild_id: GuildId,
+    db_cfg: Arc<DatabaseConfiguration>,
+    songbird: Arc<Songbird>,
+    cache: Arc<CacheManager>,
+    /// Shared with the retry, so it doesn't count as another play.
+    recorder: PlayStatsRecorder,
+    announcer: NowPlayingAnnouncer,
+    playback: Arc<PlaybackManager>,
+    /// Playback session the track was queued in. A retry is dropped once that's over.
+    session: u64,
+    /// Taken on the first error, so each track is only retried once.
+    retry: Arc<Mutex<Option<TrackRetry>>>,
+}
+
+impl TrackErrorNotifier {
+    pub async fn new(ctx: &ExecutionContext<'_>, guild_id: GuildId, retry: Option<TrackRetry>) -> Self {
+        let db_cfg = db::share_config(ctx.db_cfg);
+        let playback = playback::get(ctx.ctx).await.expect("playback manager initialized");
+        Self {
+            http: Arc::clone(&ctx.ctx.http),
+            channel: ctx.cmd.channel_id,
+            guild_id,
+            db_cfg: Arc::clone(&db_cfg),
+            songbird: songbird::get(ctx.ctx).await.expect("songbird initialized"),
+            cache: cache::get(ctx.ctx).await.expect("cache manager initialized"),
+            recorder: PlayStatsRecorder::new(db_cfg, guild_id),
+            announcer: NowPlayingAnnouncer::new(ctx, guild_id).await,
+            playback: Arc::clone(&playback),
+            session: playback.session(guild_id),
+            retry: Arc::new(Mutex::new(retry)),
+        }
+    }
+
+    async fn say(&self, msg: String) {
+        if let Err(e) = self.channel.say(&*self.http, msg).await {
+            trc::warn!("TRACK-ERROR-NOTIFY-FAIL {} {e:?}", self.guild_id);
+        }
+    }
+
+    async fn retry_from_download(self, info: Arc<TrackInfo>, video_id: String, mut download: watch::Receiver<DownloadStatus>) {
+        let finished = match tokio::time::timeout(RETRY_DOWNLOAD_WAIT, download.wait_for(DownloadStatus::is_done)).await {
+            Ok(Ok(status)) => matches!(*status, DownloadStatus::Finished),
+            _ => false,
+        };
+        if !self.playback.is_current(self.guild_id, self.session) {
+            trc::info!("TRACK-RETRY-DROPPED {} {video_id:?}", self.guild_id);
+            return;
+        }
+        if !finished {
+            self.say(format!("The download of {} didn't work out either, skipping it.", info.display())).await;
+            return;
+        }
+
+        let title = info.display();
+        match self.queue_download_next(info, video_id.as_str()).await {
+            Ok(()) => {
+                trc::info!("TRACK-RETRY-QUEUED {} {video_id:?}", self.guild_id);
+                self.say(format!("Got the download of {title}, it plays next.")).await;
+            },
+            Err(_) if !self.playback.is_current(self.guild_id, self.session) => {
+                trc::info!("TRACK-RETRY-DROPPED {} {video_id:?}", self.guild_id);
+            },
+            Err(e) => {
+                trc::warn!("TRACK-RETRY-FAIL {} {video_id:?} {e:?}", self.guild_id);
+                self.say(format!("Couldn't play the download of {title} either, skipping it.")).await;
+            },
+        }
+    }
+
+    async fn queue_download_next(&self, info: Arc<TrackInfo>, video_id: &str) -> Result<(), RequestError> {
+        let lease = self.cache.lease(video_id);
+        self.cache.mark_played(&self.db_cfg, video_id).await?;
+        let input = cached_input(self.cache.downloaded_file(video_id)?).await?;
+
+        let handler = self.songbird.get(self.guild_id).ok_or_else(|| RequestError::User("no longer in voice".into()))?;
+        let mut call = handler.lock().await;
+        // Checked again under the lock, in case playback was stopped while the file loaded.
+        if !self.playback.is_current(self.guild_id, self.session) {
+            return Err(RequestError::User("playback was stopped".into()));
+        }
+        let track_handle = call.enqueue(new_track(input, TrackInfo::clone(&info))).await;
+        // Right after whatever is playing now, rather than behind everything queued since.
+        call.queue().modify_queue(|queue| {
+            if queue.len() > 2 {
+                if let Some(track) = queue.pop_back() {
+                    queue.insert(1, track);
+                }
+            }
+        });
+        drop(call);
 
-pub struct TrackErrorNotifier;
+        let notifier = Self {
+            announcer: self.announcer.for_next_track(),
+            retry: Arc::new(Mutex::new(None)),
+            ..self.clone()
+        };
+        register_track_events(&track_handle, notifier, Some(lease))
+    }
+}
 
 #[async_trait]
 impl EventHandler for TrackErrorNotifier {
     async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
-        if let EventContext::Track(track_list) = ctx {
-            for (state, handle) in *track_list {
-                trc::error!(
-                    "Track {:?} encountered an error: {:?}",
-                    handle.uuid(),
-                    state.playing
-                );
+        let EventContext::Track(track_list) = ctx else {
+            return None;
+        };
+        for (state, handle) in *track_list {
+            trc::error!(
+                "Track {:?} encountered an error: {:?}",
+                handle.uuid(),
+                state.playing
+            );
+            let info = track_info(handle);
+            let what_happened = match &state.playing {
+                PlayMode::Errored(PlayError::Create(_)) => "couldn't be loaded",
+                _ => "stopped working",
+            };
+
+            let retry = self.retry.lock().expect("not poisoned").take();
+            match retry {
+                Some(TrackRetry::FromDownload { video_id, download }) => {
+                    self.say(format!(
+                        "{}'s {} {what_happened} while streaming, I'll try again from the download once it's done.",
+                        Mention::User(info.requester), info.display(),
+                    )).await;
+                    tokio::spawn(self.clone().retry_from_download(info, video_id, download));
+                },
+                None => {
+                    self.say(format!("{}'s {} {what_happened}, skipping it.", Mention::User(info.requester), info.display())).await;
+                },
+            }
+        }
+        None
+    }
+}
+
+/// Hooks up everything a queued track needs: error reporting, announcements, play stats and, for cached
+/// files, the lease keeping the file around.
+pub fn register_track_events(track_handle: &TrackHandle, notifier: TrackErrorNotifier, lease: Option<CacheLease>) -> Result<(), RequestError> {
+    let recorder = notifier.recorder.clone();
+    let announcer = notifier.announcer.clone();
+    track_handle.add_event(Event::Track(TrackEvent::Error), notifier)
+        .map_err(|e| RequestError::Internal(format!("failure to set error handler {e:?}").into()))?;
+    for event in [TrackEvent::Play, TrackEvent::End] {
+        track_handle.add_event(Event::Track(event), announcer.clone())
+            .map_err(|e| RequestError::Internal(format!("failure to set announcement handler {e:?}").into()))?;
+    }
+    for event in [TrackEvent::Play, TrackEvent::End] {
+        track_handle.add_event(Event::Track(event), recorder.clone())
+            .map_err(|e| RequestError::Internal(format!("failure to set stats handler {e:?}").into()))?;
+    }
+    if let Some(lease) = lease {
+        // Keep the cached file around until playback is done with it.
+        let releaser = CacheLeaseReleaser::new(lease);
+        for event in [TrackEvent::End, TrackEvent::Error] {
+            track_handle.add_event(Event::Track(event), releaser.clone())
+                .map_err(|e| RequestError::Internal(format!("failure to set cache handler {e:?}").into()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Releases a track's [`CacheLease`] once it ends or errors, whichever comes first.
+#[derive(Clone)]
+pub struct CacheLeaseReleaser(Arc<Mutex<Option<CacheLease>>>);
+
+impl CacheLeaseReleaser {
+    pub fn new(lease: CacheLease) -> Self {
+        Self(Arc::new(Mutex::new(Some(lease))))
+    }
+}
+
+#[async_trait]
+impl EventHandler for CacheLeaseReleaser {
+    async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+        drop(self.0.lock().expect("lease lock not poisoned").take());
+        Some(Event::Cancel)
+    }
+}
+
+/// Records a play event when a track first starts, and another for how it ended. Register for both `Play` and `End`.
+#[derive(Clone)]
+pub struct PlayStatsRecorder {
+    db_cfg: Arc<DatabaseConfiguration>,
+    guild_id: GuildId,
+    started: Arc<AtomicBool>,
+}
+
+impl PlayStatsRecorder {
+    pub fn new(db_cfg: Arc<DatabaseConfiguration>, guild_id: GuildId) -> Self {
+        Self {
+            db_cfg,
+            guild_id,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for PlayStatsRecorder {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let EventContext::Track(track_list) = ctx else {
+            return None;
+        };
+        for (state, handle) in *track_list {
+            let kind = match state.playing {
+                PlayMode::Play if !self.started.swap(true, Ordering::Relaxed) => PlayEventKind::Start,
+                // Resuming after a pause isn't a new play.
+                PlayMode::Play => continue,
+                // Never started, so there's no play for it to end.
+                PlayMode::End | PlayMode::Stop if !self.started.load(Ordering::Relaxed) => continue,
+                PlayMode::End => PlayEventKind::Complete,
+                PlayMode::Stop => PlayEventKind::Skip,
+                _ => continue,
+            };
+            let info = track_info(handle);
+
+            let recorded = stats::record_play_event(&self.db_cfg, &NewPlayEvent {
+                guild_id: u64::from(self.guild_id).into(),
+                user_id: u64::from(info.requester).into(),
+                source: info.source.as_str(),
+                title: info.title.as_str(),
+                kind: kind.as_str(),
+                seconds_listened: state.play_time.as_secs_f64(),
+            }).await;
+            if let Err(e) = recorded {
+                trc::warn!("PLAY-STATS-RECORD-FAIL {:?} {e:?}", handle.uuid());
             }
         }
         None
@@ -0,0 +1,194 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use chrono::Utc;
+use serenity::{all::Context, prelude::TypeMapKey};
+use tracing as trc;
+
+use crate::{db::cache::{self as db_cache, CachedDownload, NewCachedDownload}, download::STAGING_PREFIX, settings::CacheSettings};
+
+pub struct CacheManagerKey;
+
+impl TypeMapKey for CacheManagerKey {
+    type Value = Arc<CacheManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<CacheManager>> {
+    ctx.data.read().await.get::<CacheManagerKey>().cloned()
+}
+
+/// Keeps `downloads/yt` under a byte budget, evicting the least recently played downloads first.
+///
+/// Sizes and play times live in the `download_cache` table. Which entries are currently playing is only
+/// tracked in memory, since it doesn't survive a restart anyways.
+#[derive(Debug)]
+pub struct CacheManager {
+    directory: PathBuf,
+    budget_bytes: u64,
+    playing: Mutex<HashMap<String, usize>>,
+}
+
+#[derive(Debug)]
+pub struct CacheStats {
+    pub budget_bytes: u64,
+    pub total_bytes: u64,
+    pub entries: Vec<CachedDownload>,
+    pub playing: usize,
+}
+
+/// Held for as long as a cached file is in use. Dropping it makes the entry evictable again.
+#[derive(Debug)]
+pub struct CacheLease {
+    manager: Arc<CacheManager>,
+    video_id: String,
+}
+
+impl Drop for CacheLease {
+    fn drop(&mut self) {
+        let mut playing = self.manager.playing.lock().expect("cache lock not poisoned");
+        if let Some(count) = playing.get_mut(&self.video_id) {
+            *count -= 1;
+            if *count == 0 {
+                playing.remove(&self.video_id);
+            }
+        }
+    }
+}
+
+impl CacheManager {
+    pub fn new(settings: &CacheSettings) -> Self {
+        Self {
+            directory: settings.directory.clone(),
+            budget_bytes: settings.budget_bytes,
+            playing: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn entry_dir(&self, video_id: &str) -> PathBuf {
+        self.directory.join(video_id)
+    }
+
+    /// The downloaded file of an entry. Downloads only ever leave the one file in their directory.
+    pub fn downloaded_file(&self, video_id: &str) -> Result<PathBuf, RequestError> {
+        let contents = std::fs::read_dir(self.entry_dir(video_id)).map_err(|_e| RequestError::Internal("video dl failure".into()))?;
+        let mut downloaded_vid_path = None;
+        for value in contents {
+            downloaded_vid_path = Some(value.map_err(|_e| RequestError::Internal("dl files check failed".into()))?.path());
+        }
+        downloaded_vid_path.ok_or_else(|| RequestError::Internal("dl failed".into()))
+    }
+
+    pub fn lease(self: &Arc<Self>, video_id: &str) -> CacheLease {
+        *self.playing.lock().expect("cache lock not poisoned").entry(video_id.to_owned()).or_default() += 1;
+        CacheLease {
+            manager: Arc::clone(self),
+            video_id: video_id.to_owned(),
+        }
+    }
+
+    /// Deletes downloads that were interrupted by a restart. They were never renamed into place, so nothing
+    /// references them.
+    pub fn remove_stale_downloads(&self) {
+        let Ok(contents) = std::fs::read_dir(self.directory.as_path()) else {
+            return;
+        };
+        for entry in contents.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(STAGING_PREFIX) {
+                trc::info!("CACHE-STALE-REMOVE {:?}", entry.path());
+                if let Err(e) = std::fs::remove_dir_all(entry.path()) {
+                    trc::warn!("CACHE-STALE-REMOVE-FAIL {:?} {e:?}", entry.path());
+                }
+            }
+        }
+    }
+
+    fn is_playing(&self, video_id: &str) -> bool {
+        self.playing.lock().expect("cache lock not poisoned").contains_key(video_id)
+    }
+
+    /// Records (or refreshes) the entry for a downloaded video as just played, then evicts anything over budget.
+    pub async fn mark_played(&self, cfg: &DatabaseConfiguration, video_id: &str) -> Result<(), RequestError> {
+        let dir = self.entry_dir(video_id);
+        let size_bytes = dir_size(dir.as_path()).map_err(|e| RequestError::Internal(format!("cache size check failed {e:?}").into()))?;
+
+        db_cache::record_cached_download(cfg, &NewCachedDownload {
+            video_id,
+            file_path: &dir.to_string_lossy(),
+            size_bytes: size_bytes as i64,
+            last_played: Utc::now(),
+        }).await?;
+
+        self.evict_to_budget(cfg).await?;
+
+        Ok(())
+    }
+
+    /// Deletes least recently played, unpinned, not currently playing entries until the cache fits the budget.
+    /// Returns the number of entries evicted.
+    pub async fn evict_to_budget(&self, cfg: &DatabaseConfiguration) -> Result<usize, RequestError> {
+        let mut total_bytes = db_cache::total_cached_download_size(cfg).await?.max(0) as u64;
+        if total_bytes <= self.budget_bytes {
+            return Ok(0);
+        }
+
+        let mut evicted = 0;
+        for entry in db_cache::load_cached_downloads_by_age(cfg).await? {
+            if total_bytes <= self.budget_bytes {
+                break;
+            }
+            if entry.pinned || self.is_playing(entry.video_id.as_str()) {
+                continue;
+            }
+
+            trc::info!("CACHE-EVICT {:?} {}", entry.video_id, entry.size_bytes);
+            match std::fs::remove_dir_all(entry.file_path.as_str()) {
+                Ok(_) => {},
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+                Err(e) => {
+                    trc::error!("CACHE-EVICT-FAIL {:?} {e:?}", entry.video_id);
+                    continue;
+                },
+            }
+            db_cache::remove_cached_download(cfg, entry.video_id.as_str()).await?;
+
+            total_bytes = total_bytes.saturating_sub(entry.size_bytes.max(0) as u64);
+            evicted += 1;
+        }
+
+        if total_bytes > self.budget_bytes {
+            trc::warn!("CACHE-OVER-BUDGET {} > {}", total_bytes, self.budget_bytes);
+        }
+
+        Ok(evicted)
+    }
+
+    pub async fn set_pinned(&self, cfg: &DatabaseConfiguration, video_id: &str, pinned: bool) -> Result<bool, RequestError> {
+        db_cache::set_cached_download_pinned(cfg, video_id, pinned).await
+    }
+
+    pub async fn stats(&self, cfg: &DatabaseConfiguration) -> Result<CacheStats, RequestError> {
+        let entries = db_cache::load_cached_downloads_by_age(cfg).await?;
+        let total_bytes = entries.iter().map(|e| e.size_bytes.max(0) as u64).sum();
+        let playing = self.playing.lock().expect("cache lock not poisoned").len();
+
+        Ok(CacheStats {
+            budget_bytes: self.budget_bytes,
+            total_bytes,
+            entries,
+            playing,
+        })
+    }
+}
+
+fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    for entry in std::fs::read_dir(path)? {
+        let metadata = entry?.metadata()?;
+        total += if metadata.is_dir() {
+            0
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(total)
+}
@@ -0,0 +1,144 @@
+use std::{collections::{HashMap, VecDeque}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
+
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use serenity::{all::{Context, GuildId, UserId}, prelude::TypeMapKey};
+use songbird::{events::context_data::VoiceTick, Call, CoreEvent, Event, EventContext, EventHandler};
+use tracing as trc;
+
+use crate::{async_trait, db::{self, guilds}, record::{self, Speakers, SAMPLE_RATE, TICK_SAMPLES}, settings};
+
+pub struct ClipManagerKey;
+
+impl TypeMapKey for ClipManagerKey {
+    type Value = Arc<ClipManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<ClipManager>> {
+    ctx.data.read().await.get::<ClipManagerKey>().cloned()
+}
+
+/// Rolling buffers of recent voice for `/clip`, only for guilds that enabled it and only while connected.
+#[derive(Default)]
+pub struct ClipManager {
+    buffers: Mutex<HashMap<GuildId, Arc<ClipBuffer>>>,
+}
+
+impl ClipManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn buffer(&self, guild_id: GuildId) -> Option<Arc<ClipBuffer>> {
+        self.buffers.lock().expect("not poisoned").get(&guild_id).cloned()
+    }
+
+    /// Whether voice is being buffered for a call that's still around.
+    pub fn is_listening(&self, guild_id: GuildId) -> bool {
+        self.buffers.lock().expect("not poisoned").get(&guild_id).is_some_and(|buffer| Arc::strong_count(buffer) > 1)
+    }
+
+    /// Starts buffering the call's voice, unless that's already happening.
+    pub fn listen(&self, guild_id: GuildId, call: &mut Call, db_cfg: Arc<DatabaseConfiguration>) {
+        record::set_decoding(call, true);
+        let mut buffers = self.buffers.lock().expect("not poisoned");
+        // Receivers keep their buffer alive, so only the map holding it means the call it was on is gone.
+        if buffers.get(&guild_id).is_some_and(|buffer| Arc::strong_count(buffer) > 1) {
+            return;
+        }
+
+        let max_secs = settings::get().clips.buffer_secs;
+        let buffer = Arc::new(ClipBuffer::new(db_cfg, usize::try_from(max_secs).unwrap_or(usize::MAX) * SAMPLE_RATE as usize));
+        let receiver = ClipReceiver(Arc::clone(&buffer));
+        call.add_global_event(CoreEvent::SpeakingStateUpdate.into(), receiver.clone());
+        call.add_global_event(CoreEvent::VoiceTick.into(), receiver.clone());
+        call.add_global_event(CoreEvent::DriverDisconnect.into(), receiver);
+        buffers.insert(guild_id, buffer);
+        trc::info!("CLIP-LISTEN {guild_id}");
+    }
+
+    /// Stops buffering and forgets what was buffered.
+    pub fn stop(&self, guild_id: GuildId) {
+        if let Some(buffer) = self.buffers.lock().expect("not poisoned").remove(&guild_id) {
+            buffer.stopped.store(true, Ordering::Relaxed);
+            trc::info!("CLIP-STOP {guild_id}");
+        }
+    }
+}
+
+/// Starts buffering for `/clip` if the guild enabled it.
+pub async fn listen_if_enabled(ctx: &Context, cfg: &DatabaseConfiguration, guild_id: GuildId, call: &tokio::sync::Mutex<Call>) -> Result<(), RequestError> {
+    if !guilds::load_guild_settings(cfg, u64::from(guild_id).into()).await?.clips_enabled {
+        return Ok(());
+    }
+    let clips = get(ctx).await.expect("clip manager initialized");
+    clips.listen(guild_id, &mut *call.lock().await, db::share_config(cfg));
+    Ok(())
+}
+
+/// The last so many seconds of everyone in the call mixed together, minus anyone who opted out of recording.
+pub struct ClipBuffer {
+    speakers: Speakers,
+    max_samples: usize,
+    stopped: AtomicBool,
+    samples: Mutex<VecDeque<i16>>,
+}
+
+impl ClipBuffer {
+    fn new(db_cfg: Arc<DatabaseConfiguration>, max_samples: usize) -> Self {
+        Self {
+            speakers: Speakers::new(db_cfg),
+            max_samples,
+            stopped: AtomicBool::new(false),
+            samples: Mutex::new(VecDeque::with_capacity(max_samples)),
+        }
+    }
+
+    /// Up to `secs` of the most recent audio.
+    pub fn last(&self, secs: u64) -> Vec<i16> {
+        let samples = self.samples.lock().expect("not poisoned");
+        let wanted = usize::try_from(secs).unwrap_or(usize::MAX).saturating_mul(SAMPLE_RATE as usize);
+        samples.iter().skip(samples.len().saturating_sub(wanted)).copied().collect()
+    }
+
+    fn capture(&self, tick: &VoiceTick) {
+        let mut mixed = [0i32; TICK_SAMPLES];
+        for (ssrc, data) in tick.speaking.iter() {
+            let (Some(_), Some(decoded)) = (self.speakers.recordable(*ssrc), data.decoded_voice.as_ref()) else {
+                continue;
+            };
+            for (total, sample) in mixed.iter_mut().zip(decoded.iter()) {
+                *total += i32::from(*sample);
+            }
+        }
+
+        let mut samples = self.samples.lock().expect("not poisoned");
+        // Silent ticks go in too, so the buffer always covers the same stretch of time.
+        samples.extend(mixed.iter().map(|total| (*total).clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16));
+        let excess = samples.len().saturating_sub(self.max_samples);
+        samples.drain(..excess);
+    }
+}
+
+#[derive(Clone)]
+struct ClipReceiver(Arc<ClipBuffer>);
+
+#[async_trait]
+impl EventHandler for ClipReceiver {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.0.stopped.load(Ordering::Relaxed) {
+            return Some(Event::Cancel);
+        }
+        match ctx {
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    self.0.speakers.identify(speaking.ssrc, UserId::new(user_id.0)).await;
+                }
+            },
+            EventContext::VoiceTick(tick) => self.0.capture(tick),
+            // Don't stitch together audio from either side of a reconnect.
+            EventContext::DriverDisconnect(_) => self.0.samples.lock().expect("not poisoned").clear(),
+            _ => {},
+        }
+        None
+    }
+}
@@ -0,0 +1,51 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::cache;
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    video_id: &'a str,
+    pinned: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction, pinned: bool) -> Result<Self, RequestError> {
+        let mut video_id = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "video" {
+                if let ResolvedValue::String(provided_video_id) = option.value {
+                    video_id = Some(provided_video_id);
+                }
+            }
+        }
+
+        Ok(Self {
+            video_id: video_id.ok_or_else(|| RequestError::User("missing `video` required parameter".into()))?,
+            pinned,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        super::require_owner(ctx)?;
+
+        let cache = cache::get(ctx.ctx).await.expect("cache manager initialized");
+        if !cache.set_pinned(ctx.db_cfg, self.video_id, self.pinned).await? {
+            ctx.reply_restricted(format!("`{}` isn't in the cache.", self.video_id)).await?;
+            return Ok(());
+        }
+
+        if self.pinned {
+            ctx.reply_restricted(format!("Pinned `{}`, it won't be evicted.", self.video_id)).await
+        } else {
+            ctx.reply_restricted(format!("Unpinned `{}`.", self.video_id)).await
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt::Write, marker::PhantomData};
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::cache;
+
+use super::super::RequestError;
+
+const LISTED_ENTRIES: usize = 10;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        super::require_owner(ctx)?;
+
+        let cache = cache::get(ctx.ctx).await.expect("cache manager initialized");
+        let stats = cache.stats(ctx.db_cfg).await?;
+
+        let mut msg = format!(
+            "Cache is using {} of {} across {} entries ({} pinned, {} playing).",
+            format_bytes(stats.total_bytes),
+            format_bytes(stats.budget_bytes),
+            stats.entries.len(),
+            stats.entries.iter().filter(|e| e.pinned).count(),
+            stats.playing,
+        );
+        if !stats.entries.is_empty() {
+            msg.push_str("\nNext up for eviction:");
+        }
+        for entry in stats.entries.iter().filter(|e| !e.pinned).take(LISTED_ENTRIES) {
+            // Writing to a String can't fail.
+            let _ = write!(msg, "\n- `{}` {} (last played <t:{}:R>)", entry.video_id, format_bytes(entry.size_bytes.max(0) as u64), entry.last_played.timestamp());
+        }
+
+        ctx.reply_restricted(msg).await
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
@@ -0,0 +1,19 @@
+pub mod cache_stats;
+pub mod cache_pin;
+pub mod ytdlp_update;
+pub mod ytdlp_version;
+pub mod uploads_migrate;
+pub mod uploads_transcode;
+
+use azel::{cmd::RequestError, discord::ExecutionContext};
+
+use crate::settings;
+
+/// `/admin` commands touch process-wide state, so they're limited to the configured bot owners.
+pub fn require_owner(ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+    if settings::get().owners.contains(&u64::from(ctx.cmd.user.id)) {
+        Ok(())
+    } else {
+        Err(RequestError::User("only the bot owner can do that".into()))
+    }
+}
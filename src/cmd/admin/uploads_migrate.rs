@@ -0,0 +1,130 @@
+use std::{collections::HashSet, marker::PhantomData, path::Path};
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+use tracing as trc;
+
+use crate::{db::{self, NewAudioLedgerEntry, Visibility}, limits::{shorten, with_list_within, LISTED_NAME_LENGTH, MESSAGE_LENGTH}, probe};
+
+use super::super::RequestError;
+
+/// Where `/play` used to look for sounds, as `uploads/<name>/data.mp3`. None of these made it into the ledger.
+const LEGACY_UPLOAD_DIR: &str = "uploads";
+/// Where `/upload` used to save sounds, as `uploaded/<uuid>/<filename>`. Newer uploads live under `uploaded/blobs`.
+const UPLOAD_DIR: &str = "uploaded";
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Adds ledger entries for sound files that don't have one. Files stay where they are, and since we don't
+    /// know who originally uploaded them, they're recorded as uploaded by whoever runs this.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        super::require_owner(ctx)?;
+        ctx.defer().await?;
+
+        let known_paths: HashSet<String> = db::load_ledger_file_paths(ctx.db_cfg).await?.into_iter().collect();
+
+        let mut candidates = vec![];
+        for dir in list_dirs(Path::new(LEGACY_UPLOAD_DIR)) {
+            let path = dir.join("data.mp3");
+            if path.is_file() {
+                let name = dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                candidates.push((name, path));
+            }
+        }
+        for dir in list_dirs(Path::new(UPLOAD_DIR)) {
+            let Ok(files) = std::fs::read_dir(dir.as_path()) else {
+                continue;
+            };
+            for file in files.flatten().map(|file| file.path()).filter(|path| path.is_file()) {
+                let name = file.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                candidates.push((name, file));
+            }
+        }
+
+        let mut adopted = vec![];
+        let mut skipped = vec![];
+        let mut rejected = vec![];
+        for (name, path) in candidates {
+            let file_path = path.to_string_lossy().into_owned();
+            if known_paths.contains(file_path.as_str()) {
+                continue;
+            }
+            if name.is_empty() || db::load_maybe_known_audio_in_ledger(ctx.db_cfg, u64::from(ctx.cmd.user.id).into(), name.as_str()).await?.is_some() {
+                skipped.push(file_path);
+                continue;
+            }
+
+            let probed = match std::fs::read(path.as_path()) {
+                Ok(data) => {
+                    let filename = file_path.clone();
+                    tokio::task::spawn_blocking(move || probe::probe_audio(filename.as_str(), data)).await
+                        .map_err(|e| RequestError::Internal(format!("audio probe failed {e:?}").into()))?
+                },
+                Err(e) => Err(format!("unreadable ({e})")),
+            };
+            let probe = match probed {
+                Ok(probe) => probe,
+                Err(reason) => {
+                    trc::warn!("UPLOAD-MIGRATE-REJECT {:?} {reason}", file_path);
+                    rejected.push(file_path);
+                    continue;
+                },
+            };
+
+            trc::info!("UPLOAD-MIGRATE {:?} {:?}", name, file_path);
+            db::track_known_audio_in_ledger(ctx.db_cfg, &NewAudioLedgerEntry {
+                link_or_name: name.as_str(),
+                downloaded: true,
+                file_path,
+                uploader: u64::from(ctx.cmd.user.id).into(),
+                codec: Some(probe.codec.as_str()),
+                sample_rate: probe.sample_rate.and_then(|rate| i32::try_from(rate).ok()),
+                channels: probe.channels.and_then(|channels| i16::try_from(channels).ok()),
+                duration_secs: probe.duration_secs,
+                original_file_path: None,
+                blob_hash: None,
+                // Same as sounds from before scoping: usable wherever the uploader is.
+                guild_id: None,
+                visibility: Visibility::Guild.as_str(),
+            }).await?;
+            adopted.push(name);
+        }
+
+        let mut msg = format!("Added {} sound(s) to the ledger.", adopted.len());
+        // Each list gets an even share of the message, so a long first one can't push the others out.
+        let share = MESSAGE_LENGTH.saturating_sub(msg.chars().count()) / 3;
+        let listed = |names: Vec<String>| names.iter().map(|name| shorten(name, LISTED_NAME_LENGTH)).collect();
+        if !adopted.is_empty() {
+            let max_chars = msg.chars().count() + share;
+            msg = with_list_within(msg, "Added:", listed(adopted), max_chars);
+        }
+        if !skipped.is_empty() {
+            let max_chars = msg.chars().count() + share;
+            let heading = format!("Skipped {} file(s) whose name is already taken:", skipped.len());
+            msg = with_list_within(msg, heading.as_str(), listed(skipped), max_chars);
+        }
+        if !rejected.is_empty() {
+            let max_chars = msg.chars().count() + share;
+            let heading = format!("Skipped {} file(s) that aren't playable audio:", rejected.len());
+            msg = with_list_within(msg, heading.as_str(), listed(rejected), max_chars);
+        }
+        ctx.reply_restricted(msg).await
+    }
+}
+
+fn list_dirs(path: &Path) -> Vec<std::path::PathBuf> {
+    let Ok(contents) = std::fs::read_dir(path) else {
+        return vec![];
+    };
+    contents.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect()
+}
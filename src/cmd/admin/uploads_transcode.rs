@@ -0,0 +1,86 @@
+use std::{collections::HashSet, marker::PhantomData, path::PathBuf};
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+use tracing as trc;
+
+use crate::{db::{self, AudioFileChanges}, limits::{shorten, with_list, LISTED_NAME_LENGTH}, probe, transcode::{self, CANONICAL_CODEC, CANONICAL_SAMPLE_RATE}};
+
+use super::super::RequestError;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Converts every sound in the ledger that isn't stored in the canonical format yet, one at a time.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        super::require_owner(ctx)?;
+        ctx.defer().await?;
+
+        let mut pending = db::load_audio_not_in_format(ctx.db_cfg, CANONICAL_CODEC, CANONICAL_SAMPLE_RATE as i32).await?;
+        // Names sharing a file get converted together.
+        let mut seen = HashSet::new();
+        pending.retain(|entry| seen.insert(entry.file_path.clone()));
+
+        let mut converted = 0;
+        let mut failed = vec![];
+        for entry in pending.iter() {
+            let path = PathBuf::from(entry.file_path.as_str());
+            let probed = match std::fs::read(path.as_path()) {
+                Ok(data) => {
+                    let filename = entry.file_path.clone();
+                    tokio::task::spawn_blocking(move || probe::probe_audio(filename.as_str(), data)).await
+                        .map_err(|e| format!("probe failed ({e})"))
+                        .and_then(|probed| probed)
+                },
+                Err(e) => Err(format!("unreadable ({e})")),
+            };
+            let stored = match probed {
+                Ok(probe) => transcode::canonicalize(path.as_path(), probe).await,
+                Err(e) => Err(e),
+            };
+            let stored = match stored {
+                Ok(stored) => stored,
+                Err(reason) => {
+                    trc::warn!("UPLOAD-TRANSCODE-FAIL {:?} {reason}", entry.link_or_name);
+                    failed.push(shorten(entry.link_or_name.as_str(), LISTED_NAME_LENGTH));
+                    continue;
+                },
+            };
+
+            let file_path = stored.file_path.to_string_lossy().into_owned();
+            let original_file_path = stored.kept_original().map(|path| path.to_string_lossy().into_owned());
+            let probe = &stored.probe;
+            let changes = AudioFileChanges {
+                file_path: file_path.as_str(),
+                original_file_path: original_file_path.as_deref(),
+                codec: Some(probe.codec.as_str()),
+                sample_rate: probe.sample_rate.and_then(|rate| i32::try_from(rate).ok()),
+                channels: probe.channels.and_then(|channels| i16::try_from(channels).ok()),
+                duration_secs: probe.duration_secs,
+            };
+            if let Err(e) = db::update_audio_file(ctx.db_cfg, entry.file_path.as_str(), &changes).await {
+                trc::warn!("UPLOAD-TRANSCODE-RECORD-FAIL {:?} {e:?}", entry.link_or_name);
+                stored.abort();
+                failed.push(shorten(entry.link_or_name.as_str(), LISTED_NAME_LENGTH));
+                continue;
+            }
+            stored.commit();
+            converted += 1;
+        }
+
+        let mut msg = format!("Transcoded {converted} of {} sound file(s).", pending.len());
+        if !failed.is_empty() {
+            msg = with_list(msg, "Failed:", failed);
+        }
+        ctx.reply_restricted(msg).await
+    }
+}
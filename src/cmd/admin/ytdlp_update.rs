@@ -0,0 +1,50 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::{settings, ytdlp};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    version: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut version = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "version" {
+                if let ResolvedValue::String(provided_version) = option.value {
+                    version = Some(provided_version);
+                }
+            }
+        }
+
+        Ok(Self {
+            version,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        super::require_owner(ctx)?;
+
+        // Fetching the release takes a bit.
+        ctx.defer().await?;
+
+        let manager = ytdlp::get(ctx.ctx).await.expect("ytdlp manager initialized");
+        let previous = manager.version().await;
+        let installed = manager.update(self.version).await?;
+
+        let mut msg = format!("yt-dlp updated from {} to {installed}.", previous.as_deref().unwrap_or("nothing"));
+        if installed != settings::get().ytdlp.version {
+            msg.push_str(&format!("\nThe configured version is still {}, update the config or the next restart will go back to it.", settings::get().ytdlp.version));
+        }
+        ctx.reply_restricted(msg).await
+    }
+}
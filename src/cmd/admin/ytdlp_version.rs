@@ -0,0 +1,32 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::{settings, ytdlp};
+
+use super::super::RequestError;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        super::require_owner(ctx)?;
+
+        let manager = ytdlp::get(ctx.ctx).await.expect("ytdlp manager initialized");
+        let msg = match manager.version().await {
+            Some(version) => format!("yt-dlp {version} is installed at `{}` (configured: {}).", manager.exec_path(), settings::get().ytdlp.version),
+            None => format!("yt-dlp isn't installed or doesn't run (configured: {}).", settings::get().ytdlp.version),
+        };
+        ctx.reply_restricted(msg).await
+    }
+}
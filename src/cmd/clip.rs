@@ -0,0 +1,76 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use chrono::Utc;
+use serenity::all::{CommandInteraction, ResolvedValue};
+use tracing as trc;
+
+use crate::{clip, record, settings};
+
+use super::{record::save_as_sound, RequestError};
+
+const CLIP_FILENAME: &str = "clip.wav";
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    seconds: Option<u64>,
+    name: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut seconds = None;
+        let mut name = None;
+
+        for option in cmd.data.options().iter() {
+            if option.name == "seconds" {
+                if let ResolvedValue::Integer(provided_seconds) = option.value {
+                    seconds = Some(u64::try_from(provided_seconds).ok().filter(|seconds| *seconds > 0).ok_or_else(|| {
+                        RequestError::User("`seconds` must be at least 1".into())
+                    })?);
+                }
+            }
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+        }
+
+        Ok(Self {
+            seconds,
+            name,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Saves the last few seconds of voice as a sound.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+        let clip_settings = &settings::get().clips;
+        let seconds = self.seconds.unwrap_or(clip_settings.default_secs);
+        if seconds > clip_settings.buffer_secs {
+            return Err(RequestError::User(format!("clips can be at most {} seconds", clip_settings.buffer_secs).into()));
+        }
+
+        let clips = clip::get(ctx.ctx).await.expect("clip manager initialized");
+        let Some(buffer) = clips.buffer(guild_id) else {
+            return Err(RequestError::User("nothing to clip, clips need to be on (`/server clips`) and me in voice".into()));
+        };
+        let samples = buffer.last(seconds);
+        if samples.iter().all(|sample| *sample == 0) {
+            return Err(RequestError::User("nothing was said, so there's nothing to clip".into()));
+        }
+
+        ctx.defer().await?;
+        let name = match self.name {
+            Some(name) => name.to_owned(),
+            None => format!("clip-{}", Utc::now().format("%Y%m%d-%H%M%S")),
+        };
+        trc::info!("CLIP-SAVE {guild_id} {:?} {seconds}", name);
+        let saved = save_as_sound(ctx, name.as_str(), CLIP_FILENAME, record::encode_wav(&samples)).await?;
+
+        ctx.reply(format!("Clipped the last {seconds} seconds. {saved}")).await
+    }
+}
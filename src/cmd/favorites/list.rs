@@ -0,0 +1,38 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::{cmd::playlist::describe_entry, limits::{fit_lines, MESSAGE_LENGTH}};
+
+use super::super::RequestError;
+
+const LISTED_FAVORITES: usize = 20;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let favorites = super::load_playable_favorites(ctx).await?;
+        if favorites.is_empty() {
+            ctx.reply_restricted("No favorites yet, `/like` something first.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let lines = favorites.iter().take(LISTED_FAVORITES).enumerate()
+            .map(|(idx, (rating, audio))| format!("{}. {} {}", idx + 1, describe_entry(audio), "★".repeat(usize::from(rating.unsigned_abs()))))
+            .collect();
+        let msg = fit_lines(lines, favorites.len(), MESSAGE_LENGTH);
+
+        ctx.reply_restricted(msg).await
+    }
+}
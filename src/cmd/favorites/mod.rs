@@ -0,0 +1,24 @@
+pub mod list;
+pub mod play;
+
+use std::collections::HashMap;
+
+use azel::discord::ExecutionContext;
+
+use crate::db::{ratings, AudioLedgerEntry};
+
+use super::{sound::retain_reachable, RequestError};
+
+/// The requester's favorites they could still play here, checked the same way `/play` checks sounds. Links are
+/// always the requester's own entries, so those pass through.
+pub async fn load_playable_favorites(ctx: &ExecutionContext<'_>) -> Result<Vec<(i16, AudioLedgerEntry)>, RequestError> {
+    let favorites = ratings::load_favorites(
+        ctx.db_cfg,
+        u64::from(ctx.cmd.user.id).into(),
+        ctx.cmd.guild_id.map(|guild_id| u64::from(guild_id).into()),
+    ).await?;
+
+    let rating_of: HashMap<_, _> = favorites.iter().map(|(rating, audio)| (audio.id, *rating)).collect();
+    let audio = favorites.into_iter().map(|(_, audio)| audio).collect();
+    Ok(retain_reachable(ctx, audio).await.into_iter().map(|audio| (rating_of[&audio.id], audio)).collect())
+}
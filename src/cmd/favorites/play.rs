@@ -0,0 +1,60 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{ChannelId, CommandInteraction, ResolvedValue};
+
+use crate::cmd::playlist::{queue_entries, Order};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    shuffle: bool,
+    smart: bool,
+    target: Option<ChannelId>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut shuffle = false;
+        let mut smart = false;
+        let mut target = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "shuffle" {
+                if let ResolvedValue::Boolean(provided_shuffle) = option.value {
+                    shuffle = provided_shuffle;
+                }
+            }
+            if option.name == "smart" {
+                if let ResolvedValue::Boolean(provided_smart) = option.value {
+                    smart = provided_smart;
+                }
+            }
+            if option.name == "target" {
+                if let ResolvedValue::Channel(target_channel) = option.value {
+                    target = Some(target_channel.id);
+                }
+            }
+        }
+
+        Ok(Self {
+            shuffle,
+            smart,
+            target,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let favorites = super::load_playable_favorites(ctx).await?;
+        if favorites.is_empty() {
+            ctx.reply_restricted("No favorites yet, `/like` something first.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let entries = favorites.into_iter().map(|(_, audio)| audio).collect();
+        queue_entries(ctx, "your favorites", entries, Order::from_flags(self.shuffle, self.smart), None, self.target, false).await
+    }
+}
@@ -2,6 +2,9 @@ use std::marker::PhantomData;
 
 use azel::discord::ExecutionContext;
 use serenity::all::{ChannelId, CommandInteraction, Mention, ResolvedValue};
+use tracing as trc;
+
+use crate::{clip, db, duck, voice_stats};
 
 use super::RequestError;
 
@@ -51,9 +54,20 @@ impl <'a> Request<'a> {
             current_channel = maybe_channel_to_switch_from;
         }
 
-        if let Err(_e) = manager.join(guild_id, channel.id).await {
-            return Err(RequestError::Internal("Voice channel join failed.".into()));
+        let handler = match manager.join(guild_id, channel.id).await {
+            Ok(handler) => handler,
+            Err(_e) => {
+                return Err(RequestError::Internal("Voice channel join failed.".into()));
+            },
+        };
+        if let Err(e) = clip::listen_if_enabled(ctx.ctx, ctx.db_cfg, guild_id, &handler).await {
+            trc::warn!("CLIP-LISTEN-FAIL {guild_id} {e:?}");
+        }
+        if let Err(e) = duck::listen_if_enabled(ctx.ctx, ctx.db_cfg, guild_id, &handler).await {
+            trc::warn!("DUCK-LISTEN-FAIL {guild_id} {e:?}");
         }
+        let voice_stats = voice_stats::get(ctx.ctx).await.expect("voice stats manager initialized");
+        voice_stats.listen(guild_id, &mut *handler.lock().await, db::share_config(ctx.db_cfg));
 
         match current_channel {
             Some(c) => {
@@ -3,6 +3,8 @@ use std::marker::PhantomData;
 use azel::{cmd::RequestError, discord::ExecutionContext};
 use serenity::all::CommandInteraction;
 
+use crate::{now_playing, playback};
+
 #[derive(Debug)]
 pub struct Request<'a> {
     _phantom: &'a PhantomData<()>,
@@ -22,7 +24,10 @@ impl <'a> Request<'a> {
             return Ok(());
         }
 
+        playback::get(ctx.ctx).await.expect("playback manager initialized").end_session(guild_id);
         manager.remove(guild_id).await.map_err(|_e| RequestError::Internal("Voice channel leave failed.".into()))?;
+        // Nothing is playing anymore.
+        now_playing::get(ctx.ctx).await.expect("now playing manager initialized").clear(&ctx.ctx.http, guild_id).await;
 
         ctx.reply("Left channel -- we just don't know which one (yet).".to_owned()).await?;
 
@@ -0,0 +1,38 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::ratings::LIKED_RATING;
+
+use super::{rate::rate_track, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    music: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut music = None;
+
+        for option in cmd.data.options().iter() {
+            if option.name == "music" {
+                if let ResolvedValue::String(provided_music) = option.value {
+                    music = Some(provided_music);
+                }
+            }
+        }
+
+        Ok(Self {
+            music,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Shorthand for a top rating, which also makes it a favorite.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        rate_track(ctx, self.music, LIKED_RATING).await
+    }
+}
@@ -5,10 +5,25 @@ pub mod pause;
 pub mod resume;
 pub mod stop;
 pub mod next;
+pub mod stats;
+pub mod wrapped;
+pub mod like;
+pub mod rate;
+pub mod clip;
 
 pub mod upload;
 
-use azel::{cmd::{CommandTreeTop, DiscordCommandArgs, DiscordCommandDescriptor, RawCommandOptionEntry, RequestError}, discord::ExecutionContext};
+pub mod admin;
+pub mod queue;
+pub mod sound;
+pub mod playlist;
+pub mod favorites;
+pub mod record;
+pub mod server;
+pub mod voicestats;
+
+use azel::{cmd::{CommandTreeIntermediate, CommandTreeTop, DiscordCommandArgs, DiscordCommandDescriptor, RawCommandOptionEntry, RequestError}, discord::ExecutionContext};
+use serenity::all::{CommandInteraction, CommandType, ResolvedOption, ResolvedValue};
 use tracing as trc;
 
 use strum::{EnumCount, EnumDiscriminants, EnumIter, EnumProperty};
@@ -27,6 +42,53 @@ pub enum RequestArgs<'a> {
     Stop(stop::Request<'a>),
     Next(next::Request<'a>),
     Upload(upload::Request<'a>),
+    Stats(stats::Request<'a>),
+    Wrapped(wrapped::Request<'a>),
+    Like(like::Request<'a>),
+    Rate(rate::Request<'a>),
+    Clip(clip::Request<'a>),
+    AdminCacheStats(admin::cache_stats::Request<'a>),
+    AdminCachePin(admin::cache_pin::Request<'a>),
+    AdminCacheUnpin(admin::cache_pin::Request<'a>),
+    AdminYtdlpUpdate(admin::ytdlp_update::Request<'a>),
+    AdminYtdlpVersion(admin::ytdlp_version::Request<'a>),
+    AdminUploadsMigrate(admin::uploads_migrate::Request<'a>),
+    AdminUploadsTranscode(admin::uploads_transcode::Request<'a>),
+    QueueList(queue::list::Request<'a>),
+    QueueSave(queue::save::Request<'a>),
+    QueueLoad(queue::load::Request<'a>),
+    SoundList(sound::list::Request<'a>),
+    SoundInfo(sound::info::Request<'a>),
+    SoundRename(sound::rename::Request<'a>),
+    SoundDelete(sound::delete::Request<'a>),
+    SoundDownload(sound::download::Request<'a>),
+    SoundShare(sound::share::Request<'a>),
+    SoundShuffle(sound::shuffle::Request<'a>),
+    SoundTagAdd(sound::tag::Request<'a>),
+    SoundTagRemove(sound::tag::Request<'a>),
+    SoundSearch(sound::search::Request<'a>),
+    PlaylistCreate(playlist::create::Request<'a>),
+    PlaylistAdd(playlist::add::Request<'a>),
+    PlaylistRemove(playlist::remove::Request<'a>),
+    PlaylistList(playlist::list::Request<'a>),
+    PlaylistShow(playlist::show::Request<'a>),
+    PlaylistDelete(playlist::delete::Request<'a>),
+    PlaylistPlay(playlist::play::Request<'a>),
+    PlaylistExport(playlist::export::Request<'a>),
+    PlaylistImport(playlist::import::Request<'a>),
+    FavoritesPlay(favorites::play::Request<'a>),
+    FavoritesList(favorites::list::Request<'a>),
+    RecordStart(record::start::Request<'a>),
+    RecordStop(record::stop::Request<'a>),
+    RecordOptOut(record::opt_out::Request<'a>),
+    RecordOptIn(record::opt_out::Request<'a>),
+    ServerClips(server::clips::Request<'a>),
+    ServerDucking(server::ducking::Request<'a>),
+    ServerNowPlaying(server::now_playing::Request<'a>),
+    VoiceStatsShow(voicestats::show::Request<'a>),
+    VoiceStatsOptIn(voicestats::opt_in::Request<'a>),
+    VoiceStatsOptOut(voicestats::opt_in::Request<'a>),
+    VoiceStatsPurge(voicestats::purge::Request<'a>),
 }
 
 impl DiscordCommandDescriptor for RequestKind {
@@ -43,6 +105,53 @@ impl DiscordCommandDescriptor for RequestKind {
             RequestKind::Stop => "stop",
             RequestKind::Next => "next",
             RequestKind::Upload => "upload",
+            RequestKind::Stats => "stats",
+            RequestKind::Wrapped => "wrapped",
+            RequestKind::Like => "like",
+            RequestKind::Rate => "rate",
+            RequestKind::Clip => "clip",
+            RequestKind::AdminCacheStats => "stats",
+            RequestKind::AdminCachePin => "pin",
+            RequestKind::AdminCacheUnpin => "unpin",
+            RequestKind::AdminYtdlpUpdate => "update",
+            RequestKind::AdminYtdlpVersion => "version",
+            RequestKind::AdminUploadsMigrate => "migrate",
+            RequestKind::AdminUploadsTranscode => "transcode",
+            RequestKind::QueueList => "list",
+            RequestKind::QueueSave => "save",
+            RequestKind::QueueLoad => "load",
+            RequestKind::SoundList => "list",
+            RequestKind::SoundInfo => "info",
+            RequestKind::SoundRename => "rename",
+            RequestKind::SoundDelete => "delete",
+            RequestKind::SoundDownload => "download",
+            RequestKind::SoundShare => "share",
+            RequestKind::SoundShuffle => "shuffle",
+            RequestKind::SoundTagAdd => "add",
+            RequestKind::SoundTagRemove => "remove",
+            RequestKind::SoundSearch => "search",
+            RequestKind::PlaylistCreate => "create",
+            RequestKind::PlaylistAdd => "add",
+            RequestKind::PlaylistRemove => "remove",
+            RequestKind::PlaylistList => "list",
+            RequestKind::PlaylistShow => "show",
+            RequestKind::PlaylistDelete => "delete",
+            RequestKind::PlaylistPlay => "play",
+            RequestKind::PlaylistExport => "export",
+            RequestKind::PlaylistImport => "import",
+            RequestKind::FavoritesPlay => "play",
+            RequestKind::FavoritesList => "list",
+            RequestKind::RecordStart => "start",
+            RequestKind::RecordStop => "stop",
+            RequestKind::RecordOptOut => "optout",
+            RequestKind::RecordOptIn => "optin",
+            RequestKind::ServerClips => "clips",
+            RequestKind::ServerDucking => "ducking",
+            RequestKind::ServerNowPlaying => "nowplaying",
+            RequestKind::VoiceStatsShow => "show",
+            RequestKind::VoiceStatsOptIn => "optin",
+            RequestKind::VoiceStatsOptOut => "optout",
+            RequestKind::VoiceStatsPurge => "purge",
         }
     }
 
@@ -57,6 +166,53 @@ impl DiscordCommandDescriptor for RequestKind {
             RequestKind::Stop => "Stops Yamble from playing audio",
             RequestKind::Next => "Play the next thing in the queue.",
             RequestKind::Upload => "Upload an audio snippet that Yamble can play",
+            RequestKind::Stats => "Show what gets played the most, and when.",
+            RequestKind::Wrapped => "Look back on a year of listening.",
+            RequestKind::Like => "Add a track to your favorites.",
+            RequestKind::Rate => "Rate a track from 1 to 5 stars.",
+            RequestKind::Clip => "Save the last few seconds of voice as a sound.",
+            RequestKind::AdminCacheStats => "Show how much space downloads are taking up.",
+            RequestKind::AdminCachePin => "Keep a download from ever being evicted.",
+            RequestKind::AdminCacheUnpin => "Allow a pinned download to be evicted again.",
+            RequestKind::AdminYtdlpUpdate => "Fetch, verify and swap in a new yt-dlp binary.",
+            RequestKind::AdminYtdlpVersion => "Show the installed yt-dlp version.",
+            RequestKind::AdminUploadsMigrate => "Add sound files that aren't in the ledger yet, so /play can find them.",
+            RequestKind::AdminUploadsTranscode => "Convert stored sounds to Ogg/Opus at 48kHz.",
+            RequestKind::QueueList => "Show what's playing and what's up next.",
+            RequestKind::QueueSave => "Save the queue as one of your playlists.",
+            RequestKind::QueueLoad => "Queue up one of your playlists.",
+            RequestKind::SoundList => "List the sounds you can play here.",
+            RequestKind::SoundInfo => "Show details about a sound.",
+            RequestKind::SoundRename => "Rename one of your sounds.",
+            RequestKind::SoundDelete => "Delete a sound. Only its uploader or an admin can do this.",
+            RequestKind::SoundDownload => "Get a sound back as a file.",
+            RequestKind::SoundShare => "Choose who can play one of your sounds.",
+            RequestKind::SoundShuffle => "Queue random sounds, favoring ones the people listening rated highly.",
+            RequestKind::SoundTagAdd => "Tag a sound so it's easier to find.",
+            RequestKind::SoundTagRemove => "Take tags off a sound.",
+            RequestKind::SoundSearch => "Find sounds by name, tags, uploader or length.",
+            RequestKind::PlaylistCreate => "Make a new, empty playlist.",
+            RequestKind::PlaylistAdd => "Add a youtube url, a sound, or what's playing now to a playlist.",
+            RequestKind::PlaylistRemove => "Remove an entry from a playlist.",
+            RequestKind::PlaylistList => "List your playlists.",
+            RequestKind::PlaylistShow => "Show what's in a playlist.",
+            RequestKind::PlaylistDelete => "Delete a playlist.",
+            RequestKind::PlaylistPlay => "Queue up everything in a playlist.",
+            RequestKind::PlaylistExport => "Get a playlist as a JSON or M3U file.",
+            RequestKind::PlaylistImport => "Make a playlist from a JSON or M3U file.",
+            RequestKind::FavoritesPlay => "Queue up everything you rated 4 stars or more.",
+            RequestKind::FavoritesList => "List your favorites.",
+            RequestKind::RecordStart => "Start recording a voice channel. Everyone there gets told.",
+            RequestKind::RecordStop => "Stop recording and post what was recorded.",
+            RequestKind::RecordOptOut => "Never be included in recordings.",
+            RequestKind::RecordOptIn => "Be included in recordings again.",
+            RequestKind::ServerClips => "Let Yamble keep recent voice around so it can be clipped.",
+            RequestKind::ServerDucking => "Turn the music down while people are talking.",
+            RequestKind::ServerNowPlaying => "Announce each track as it starts playing.",
+            RequestKind::VoiceStatsShow => "Show how long someone spoke in voice.",
+            RequestKind::VoiceStatsOptIn => "Have how long you speak in voice counted.",
+            RequestKind::VoiceStatsOptOut => "Stop counting how long you speak in voice.",
+            RequestKind::VoiceStatsPurge => "Delete all your counted speaking time.",
         }
     }
 
@@ -101,6 +257,345 @@ impl DiscordCommandDescriptor for RequestKind {
                     required: false,
                 }
             ],
+            RequestKind::Stats => vec![
+                RawCommandOptionEntry::User {
+                    name: "user",
+                    description: "Only count plays this user requested",
+                    required: false,
+                }, RawCommandOptionEntry::Integer {
+                    name: "days",
+                    description: "Only count plays from this many days back. Defaults to all time.",
+                    required: false,
+                }, RawCommandOptionEntry::String {
+                    name: "timezone",
+                    description: "Time zone for busiest times, e.g. Europe/Berlin",
+                    required: false,
+                },
+            ],
+            RequestKind::Wrapped => vec![
+                RawCommandOptionEntry::Integer {
+                    name: "year",
+                    description: "Year to look back on. Defaults to this one.",
+                    required: false,
+                }, RawCommandOptionEntry::User {
+                    name: "user",
+                    description: "Whose recap to show. Defaults to yours.",
+                    required: false,
+                }, RawCommandOptionEntry::String {
+                    name: "timezone",
+                    description: "Time zone for days and hours, e.g. Europe/Berlin",
+                    required: false,
+                },
+            ],
+            RequestKind::Like => vec![
+                RawCommandOptionEntry::String {
+                    name: "music",
+                    description: "Youtube url or name of uploaded sound. Defaults to what's playing now.",
+                    required: false,
+                },
+            ],
+            RequestKind::Rate => vec![
+                RawCommandOptionEntry::Integer {
+                    name: "stars",
+                    description: "From 1 to 5",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "music",
+                    description: "Youtube url or name of uploaded sound. Defaults to what's playing now.",
+                    required: false,
+                },
+            ],
+            RequestKind::AdminCacheStats => vec![],
+            RequestKind::AdminCachePin | RequestKind::AdminCacheUnpin => vec![
+                RawCommandOptionEntry::String {
+                    name: "video",
+                    description: "Youtube video id of the download",
+                    required: true,
+                },
+            ],
+            RequestKind::AdminYtdlpUpdate => vec![
+                RawCommandOptionEntry::String {
+                    name: "version",
+                    description: "Release to install, e.g. 2024.12.13. Defaults to the latest release.",
+                    required: false,
+                },
+            ],
+            RequestKind::AdminYtdlpVersion => vec![],
+            RequestKind::AdminUploadsMigrate => vec![],
+            RequestKind::AdminUploadsTranscode => vec![],
+            RequestKind::QueueList => vec![],
+            RequestKind::QueueSave => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name of the playlist to save to",
+                    required: true,
+                }, RawCommandOptionEntry::Boolean {
+                    name: "overwrite",
+                    description: "Replace the playlist if you already have one with this name.",
+                    required: false,
+                },
+            ],
+            RequestKind::QueueLoad => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name of your playlist",
+                    required: true,
+                }, RawCommandOptionEntry::Boolean {
+                    name: "replace",
+                    description: "Clear the queue first. By default the playlist is queued at the end.",
+                    required: false,
+                },
+            ],
+            RequestKind::SoundList => vec![
+                RawCommandOptionEntry::Integer {
+                    name: "page",
+                    description: "Page to show, starting at 1",
+                    required: false,
+                }, RawCommandOptionEntry::User {
+                    name: "uploader",
+                    description: "Only show sounds uploaded by this user",
+                    required: false,
+                },
+            ],
+            RequestKind::SoundInfo | RequestKind::SoundDelete | RequestKind::SoundDownload => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name of the sound",
+                    required: true,
+                },
+            ],
+            RequestKind::SoundRename => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name of your sound",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "new_name",
+                    description: "Name to give it instead",
+                    required: true,
+                },
+            ],
+            RequestKind::SoundShare => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name of your sound",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "visibility",
+                    description: "private (just you), guild (this server) or public (everyone)",
+                    required: true,
+                },
+            ],
+            RequestKind::PlaylistCreate => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name for the playlist",
+                    required: true,
+                },
+            ],
+            RequestKind::PlaylistAdd => vec![
+                RawCommandOptionEntry::String {
+                    name: "playlist",
+                    description: "Name of your playlist",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "music",
+                    description: "Youtube url or name of uploaded sound. Defaults to what's playing now.",
+                    required: false,
+                },
+            ],
+            RequestKind::PlaylistRemove => vec![
+                RawCommandOptionEntry::String {
+                    name: "playlist",
+                    description: "Name of your playlist",
+                    required: true,
+                }, RawCommandOptionEntry::Integer {
+                    name: "position",
+                    description: "Number of the entry, as shown by /playlist show",
+                    required: true,
+                },
+            ],
+            RequestKind::PlaylistList => vec![],
+            RequestKind::PlaylistShow | RequestKind::PlaylistDelete => vec![
+                RawCommandOptionEntry::String {
+                    name: "playlist",
+                    description: "Name of your playlist",
+                    required: true,
+                },
+            ],
+            RequestKind::PlaylistPlay => vec![
+                RawCommandOptionEntry::String {
+                    name: "playlist",
+                    description: "Name of your playlist",
+                    required: true,
+                }, RawCommandOptionEntry::Boolean {
+                    name: "shuffle",
+                    description: "Queue the entries in a random order.",
+                    required: false,
+                }, RawCommandOptionEntry::Boolean {
+                    name: "smart",
+                    description: "Shuffle, favoring what the people listening rated highly.",
+                    required: false,
+                }, RawCommandOptionEntry::Channel {
+                    name: "target",
+                    description: "Channel to join",
+                    required: false,
+                },
+            ],
+            RequestKind::FavoritesPlay => vec![
+                RawCommandOptionEntry::Boolean {
+                    name: "shuffle",
+                    description: "Queue your favorites in a random order.",
+                    required: false,
+                }, RawCommandOptionEntry::Boolean {
+                    name: "smart",
+                    description: "Shuffle, favoring what the people listening rated highly.",
+                    required: false,
+                }, RawCommandOptionEntry::Channel {
+                    name: "target",
+                    description: "Channel to join",
+                    required: false,
+                },
+            ],
+            RequestKind::FavoritesList => vec![],
+            RequestKind::RecordStart => vec![
+                RawCommandOptionEntry::Channel {
+                    name: "target",
+                    description: "Channel to record. Defaults to the one you're in.",
+                    required: false,
+                },
+            ],
+            RequestKind::RecordStop => vec![
+                RawCommandOptionEntry::String {
+                    name: "save_as",
+                    description: "Also save the recording as a sound with this name",
+                    required: false,
+                },
+            ],
+            RequestKind::RecordOptOut | RequestKind::RecordOptIn => vec![],
+            RequestKind::Clip => vec![
+                RawCommandOptionEntry::Integer {
+                    name: "seconds",
+                    description: "How far back to clip",
+                    required: false,
+                }, RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name to save the clip as",
+                    required: false,
+                },
+            ],
+            RequestKind::ServerClips => vec![
+                RawCommandOptionEntry::Boolean {
+                    name: "enabled",
+                    description: "Whether to keep recent voice around for /clip",
+                    required: true,
+                },
+            ],
+            RequestKind::ServerDucking => vec![
+                RawCommandOptionEntry::Boolean {
+                    name: "enabled",
+                    description: "Whether to turn the music down while people talk",
+                    required: true,
+                }, RawCommandOptionEntry::Integer {
+                    name: "volume",
+                    description: "How loud the music stays while ducked, in percent",
+                    required: false,
+                },
+            ],
+            RequestKind::ServerNowPlaying => vec![
+                RawCommandOptionEntry::String {
+                    name: "mode",
+                    description: "off, compact or embed",
+                    required: true,
+                }, RawCommandOptionEntry::Channel {
+                    name: "channel",
+                    description: "Where to announce. Keeps the current channel if left out.",
+                    required: false,
+                }, RawCommandOptionEntry::Boolean {
+                    name: "requested_channel",
+                    description: "Announce where each track was requested again, rather than in one channel.",
+                    required: false,
+                },
+            ],
+            RequestKind::VoiceStatsShow => vec![
+                RawCommandOptionEntry::User {
+                    name: "user",
+                    description: "Whose speaking time to show. Defaults to yours.",
+                    required: false,
+                }, RawCommandOptionEntry::Integer {
+                    name: "days",
+                    description: "How many days back to look. Defaults to 30.",
+                    required: false,
+                },
+            ],
+            RequestKind::VoiceStatsOptIn | RequestKind::VoiceStatsOptOut | RequestKind::VoiceStatsPurge => vec![],
+            RequestKind::SoundTagAdd | RequestKind::SoundTagRemove => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name of the sound",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "tags",
+                    description: "Comma or space separated, e.g. meme, loud",
+                    required: true,
+                },
+            ],
+            RequestKind::SoundSearch => vec![
+                RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "All or part of the name, typos are fine",
+                    required: false,
+                }, RawCommandOptionEntry::String {
+                    name: "tags",
+                    description: "Only sounds with all of these tags",
+                    required: false,
+                }, RawCommandOptionEntry::User {
+                    name: "uploader",
+                    description: "Only sounds uploaded by this user",
+                    required: false,
+                }, RawCommandOptionEntry::Integer {
+                    name: "min_duration",
+                    description: "Shortest length, in seconds",
+                    required: false,
+                }, RawCommandOptionEntry::Integer {
+                    name: "max_duration",
+                    description: "Longest length, in seconds",
+                    required: false,
+                },
+            ],
+            RequestKind::SoundShuffle => vec![
+                RawCommandOptionEntry::Integer {
+                    name: "count",
+                    description: "How many sounds to queue, up to 50. Defaults to 10.",
+                    required: false,
+                }, RawCommandOptionEntry::Channel {
+                    name: "target",
+                    description: "Channel to join",
+                    required: false,
+                },
+            ],
+            RequestKind::PlaylistExport => vec![
+                RawCommandOptionEntry::String {
+                    name: "playlist",
+                    description: "Name of your playlist",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "format",
+                    description: "json (default, keeps video details) or m3u",
+                    required: false,
+                },
+            ],
+            RequestKind::PlaylistImport => vec![
+                RawCommandOptionEntry::Attachment {
+                    name: "file",
+                    description: "Playlist file, as JSON or M3U",
+                    required: true,
+                }, RawCommandOptionEntry::String {
+                    name: "name",
+                    description: "Name for the new playlist. Defaults to the one in the file.",
+                    required: false,
+                },
+            ],
         }
     }
 
@@ -115,10 +610,103 @@ impl DiscordCommandDescriptor for RequestKind {
             "stop" => Ok(RequestArgs::Stop(stop::Request::parse(cmd)?)),
             "next" => Ok(RequestArgs::Next(next::Request::parse(cmd)?)),
             "upload" => Ok(RequestArgs::Upload(upload::Request::parse(cmd)?)),
-            _ => {
-                trc::error!("Unknown command {:?} received", cmd);
-                Err(RequestError::Internal("Unknown command.".into()))
+            "stats" => Ok(RequestArgs::Stats(stats::Request::parse(cmd)?)),
+            "wrapped" => Ok(RequestArgs::Wrapped(wrapped::Request::parse(cmd)?)),
+            "like" => Ok(RequestArgs::Like(like::Request::parse(cmd)?)),
+            "rate" => Ok(RequestArgs::Rate(rate::Request::parse(cmd)?)),
+            "clip" => Ok(RequestArgs::Clip(clip::Request::parse(cmd)?)),
+            "admin" => match resolve_subcommand(cmd).0.as_slice() {
+                ["cache", "stats"] => Ok(RequestArgs::AdminCacheStats(admin::cache_stats::Request::parse(cmd)?)),
+                ["cache", "pin"] => Ok(RequestArgs::AdminCachePin(admin::cache_pin::Request::parse(cmd, true)?)),
+                ["cache", "unpin"] => Ok(RequestArgs::AdminCacheUnpin(admin::cache_pin::Request::parse(cmd, false)?)),
+                ["ytdlp", "update"] => Ok(RequestArgs::AdminYtdlpUpdate(admin::ytdlp_update::Request::parse(cmd)?)),
+                ["ytdlp", "version"] => Ok(RequestArgs::AdminYtdlpVersion(admin::ytdlp_version::Request::parse(cmd)?)),
+                ["uploads", "migrate"] => Ok(RequestArgs::AdminUploadsMigrate(admin::uploads_migrate::Request::parse(cmd)?)),
+                ["uploads", "transcode"] => Ok(RequestArgs::AdminUploadsTranscode(admin::uploads_transcode::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
+            },
+            "queue" => match resolve_subcommand(cmd).0.as_slice() {
+                ["list"] => Ok(RequestArgs::QueueList(queue::list::Request::parse(cmd)?)),
+                ["save"] => Ok(RequestArgs::QueueSave(queue::save::Request::parse(cmd)?)),
+                ["load"] => Ok(RequestArgs::QueueLoad(queue::load::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
+            },
+            "sound" => match resolve_subcommand(cmd).0.as_slice() {
+                ["list"] => Ok(RequestArgs::SoundList(sound::list::Request::parse(cmd)?)),
+                ["info"] => Ok(RequestArgs::SoundInfo(sound::info::Request::parse(cmd)?)),
+                ["rename"] => Ok(RequestArgs::SoundRename(sound::rename::Request::parse(cmd)?)),
+                ["delete"] => Ok(RequestArgs::SoundDelete(sound::delete::Request::parse(cmd)?)),
+                ["download"] => Ok(RequestArgs::SoundDownload(sound::download::Request::parse(cmd)?)),
+                ["share"] => Ok(RequestArgs::SoundShare(sound::share::Request::parse(cmd)?)),
+                ["shuffle"] => Ok(RequestArgs::SoundShuffle(sound::shuffle::Request::parse(cmd)?)),
+                ["tag", "add"] => Ok(RequestArgs::SoundTagAdd(sound::tag::Request::parse(cmd, true)?)),
+                ["tag", "remove"] => Ok(RequestArgs::SoundTagRemove(sound::tag::Request::parse(cmd, false)?)),
+                ["search"] => Ok(RequestArgs::SoundSearch(sound::search::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
+            },
+            "playlist" => match resolve_subcommand(cmd).0.as_slice() {
+                ["create"] => Ok(RequestArgs::PlaylistCreate(playlist::create::Request::parse(cmd)?)),
+                ["add"] => Ok(RequestArgs::PlaylistAdd(playlist::add::Request::parse(cmd)?)),
+                ["remove"] => Ok(RequestArgs::PlaylistRemove(playlist::remove::Request::parse(cmd)?)),
+                ["list"] => Ok(RequestArgs::PlaylistList(playlist::list::Request::parse(cmd)?)),
+                ["show"] => Ok(RequestArgs::PlaylistShow(playlist::show::Request::parse(cmd)?)),
+                ["delete"] => Ok(RequestArgs::PlaylistDelete(playlist::delete::Request::parse(cmd)?)),
+                ["play"] => Ok(RequestArgs::PlaylistPlay(playlist::play::Request::parse(cmd)?)),
+                ["export"] => Ok(RequestArgs::PlaylistExport(playlist::export::Request::parse(cmd)?)),
+                ["import"] => Ok(RequestArgs::PlaylistImport(playlist::import::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
+            },
+            "favorites" => match resolve_subcommand(cmd).0.as_slice() {
+                ["play"] => Ok(RequestArgs::FavoritesPlay(favorites::play::Request::parse(cmd)?)),
+                ["list"] => Ok(RequestArgs::FavoritesList(favorites::list::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
+            },
+            "record" => match resolve_subcommand(cmd).0.as_slice() {
+                ["start"] => Ok(RequestArgs::RecordStart(record::start::Request::parse(cmd)?)),
+                ["stop"] => Ok(RequestArgs::RecordStop(record::stop::Request::parse(cmd)?)),
+                ["optout"] => Ok(RequestArgs::RecordOptOut(record::opt_out::Request::parse(cmd, true)?)),
+                ["optin"] => Ok(RequestArgs::RecordOptIn(record::opt_out::Request::parse(cmd, false)?)),
+                _ => unknown_command(cmd),
+            },
+            "server" => match resolve_subcommand(cmd).0.as_slice() {
+                ["clips"] => Ok(RequestArgs::ServerClips(server::clips::Request::parse(cmd)?)),
+                ["ducking"] => Ok(RequestArgs::ServerDucking(server::ducking::Request::parse(cmd)?)),
+                ["nowplaying"] => Ok(RequestArgs::ServerNowPlaying(server::now_playing::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
             },
+            "voicestats" => match resolve_subcommand(cmd).0.as_slice() {
+                ["show"] => Ok(RequestArgs::VoiceStatsShow(voicestats::show::Request::parse(cmd)?)),
+                ["optin"] => Ok(RequestArgs::VoiceStatsOptIn(voicestats::opt_in::Request::parse(cmd, true)?)),
+                ["optout"] => Ok(RequestArgs::VoiceStatsOptOut(voicestats::opt_in::Request::parse(cmd, false)?)),
+                ["purge"] => Ok(RequestArgs::VoiceStatsPurge(voicestats::purge::Request::parse(cmd)?)),
+                _ => unknown_command(cmd),
+            },
+            _ => unknown_command(cmd),
+        }
+    }
+}
+
+fn unknown_command<T>(cmd: &CommandInteraction) -> Result<T, RequestError> {
+    trc::error!("Unknown command {:?} received", cmd);
+    Err(RequestError::Internal("Unknown command.".into()))
+}
+
+/// Walks down any subcommand groups/subcommands, returning the path taken (e.g. `["cache", "pin"]`) along with the
+/// options passed to the innermost subcommand.
+pub fn resolve_subcommand(cmd: &CommandInteraction) -> (Vec<&str>, Vec<ResolvedOption<'_>>) {
+    let mut path = vec![];
+    let mut options = cmd.data.options();
+    loop {
+        match options.pop() {
+            Some(ResolvedOption { name, value: ResolvedValue::SubCommand(inner) | ResolvedValue::SubCommandGroup(inner), .. }) if options.is_empty() => {
+                path.push(name);
+                options = inner;
+            },
+            Some(option) => {
+                options.push(option);
+                return (path, options);
+            },
+            None => return (path, options),
         }
     }
 }
@@ -138,6 +726,53 @@ impl <'a> DiscordCommandArgs for RequestArgs<'a> {
             RequestArgs::Stop(req) => req.execute(ctx).await,
             RequestArgs::Next(req) => req.execute(ctx).await,
             RequestArgs::Upload(req) => req.execute(ctx).await,
+            RequestArgs::Stats(req) => req.execute(ctx).await,
+            RequestArgs::Wrapped(req) => req.execute(ctx).await,
+            RequestArgs::Like(req) => req.execute(ctx).await,
+            RequestArgs::Rate(req) => req.execute(ctx).await,
+            RequestArgs::Clip(req) => req.execute(ctx).await,
+            RequestArgs::AdminCacheStats(req) => req.execute(ctx).await,
+            RequestArgs::AdminCachePin(req) => req.execute(ctx).await,
+            RequestArgs::AdminCacheUnpin(req) => req.execute(ctx).await,
+            RequestArgs::AdminYtdlpUpdate(req) => req.execute(ctx).await,
+            RequestArgs::AdminYtdlpVersion(req) => req.execute(ctx).await,
+            RequestArgs::AdminUploadsMigrate(req) => req.execute(ctx).await,
+            RequestArgs::AdminUploadsTranscode(req) => req.execute(ctx).await,
+            RequestArgs::QueueList(req) => req.execute(ctx).await,
+            RequestArgs::QueueSave(req) => req.execute(ctx).await,
+            RequestArgs::QueueLoad(req) => req.execute(ctx).await,
+            RequestArgs::SoundList(req) => req.execute(ctx).await,
+            RequestArgs::SoundInfo(req) => req.execute(ctx).await,
+            RequestArgs::SoundRename(req) => req.execute(ctx).await,
+            RequestArgs::SoundDelete(req) => req.execute(ctx).await,
+            RequestArgs::SoundDownload(req) => req.execute(ctx).await,
+            RequestArgs::SoundShare(req) => req.execute(ctx).await,
+            RequestArgs::SoundShuffle(req) => req.execute(ctx).await,
+            RequestArgs::SoundTagAdd(req) => req.execute(ctx).await,
+            RequestArgs::SoundTagRemove(req) => req.execute(ctx).await,
+            RequestArgs::SoundSearch(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistCreate(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistAdd(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistRemove(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistList(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistShow(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistDelete(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistPlay(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistExport(req) => req.execute(ctx).await,
+            RequestArgs::PlaylistImport(req) => req.execute(ctx).await,
+            RequestArgs::FavoritesPlay(req) => req.execute(ctx).await,
+            RequestArgs::FavoritesList(req) => req.execute(ctx).await,
+            RequestArgs::RecordStart(req) => req.execute(ctx).await,
+            RequestArgs::RecordStop(req) => req.execute(ctx).await,
+            RequestArgs::RecordOptOut(req) => req.execute(ctx).await,
+            RequestArgs::RecordOptIn(req) => req.execute(ctx).await,
+            RequestArgs::ServerClips(req) => req.execute(ctx).await,
+            RequestArgs::ServerDucking(req) => req.execute(ctx).await,
+            RequestArgs::ServerNowPlaying(req) => req.execute(ctx).await,
+            RequestArgs::VoiceStatsShow(req) => req.execute(ctx).await,
+            RequestArgs::VoiceStatsOptIn(req) => req.execute(ctx).await,
+            RequestArgs::VoiceStatsOptOut(req) => req.execute(ctx).await,
+            RequestArgs::VoiceStatsPurge(req) => req.execute(ctx).await,
         }
     }
 }
@@ -154,5 +789,149 @@ pub fn generate_command_descriptions() -> Vec<CommandTreeTop<RequestKind>> {
         CommandTreeTop::NakedChatInput(RequestKind::Stop, None),
         CommandTreeTop::NakedChatInput(RequestKind::Next, None),
         CommandTreeTop::NakedChatInput(RequestKind::Upload, None),
+        CommandTreeTop::NakedChatInput(RequestKind::Stats, None),
+        CommandTreeTop::NakedChatInput(RequestKind::Wrapped, None),
+        CommandTreeTop::NakedChatInput(RequestKind::Like, None),
+        CommandTreeTop::NakedChatInput(RequestKind::Rate, None),
+        CommandTreeTop::NakedChatInput(RequestKind::Clip, None),
+        CommandTreeTop::Complex {
+            name: "admin",
+            description: "Bot owner only maintenance commands.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![
+                CommandTreeIntermediate {
+                    name: "cache",
+                    description: "Inspect and manage the youtube download cache.",
+                    children: vec![
+                        RequestKind::AdminCacheStats,
+                        RequestKind::AdminCachePin,
+                        RequestKind::AdminCacheUnpin,
+                    ],
+                },
+                CommandTreeIntermediate {
+                    name: "ytdlp",
+                    description: "Manage the yt-dlp binary used for youtube playback.",
+                    children: vec![
+                        RequestKind::AdminYtdlpUpdate,
+                        RequestKind::AdminYtdlpVersion,
+                    ],
+                },
+                CommandTreeIntermediate {
+                    name: "uploads",
+                    description: "Maintain uploaded sounds.",
+                    children: vec![
+                        RequestKind::AdminUploadsMigrate,
+                        RequestKind::AdminUploadsTranscode,
+                    ],
+                },
+            ],
+            subcommands: vec![],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "queue",
+            description: "Look at and manage the playback queue.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![],
+            subcommands: vec![
+                RequestKind::QueueList,
+                RequestKind::QueueSave,
+                RequestKind::QueueLoad,
+            ],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "sound",
+            description: "Manage uploaded sounds.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![
+                CommandTreeIntermediate {
+                    name: "tag",
+                    description: "Tag sounds so they're easier to find.",
+                    children: vec![
+                        RequestKind::SoundTagAdd,
+                        RequestKind::SoundTagRemove,
+                    ],
+                },
+            ],
+            subcommands: vec![
+                RequestKind::SoundList,
+                RequestKind::SoundInfo,
+                RequestKind::SoundRename,
+                RequestKind::SoundDelete,
+                RequestKind::SoundDownload,
+                RequestKind::SoundShare,
+                RequestKind::SoundShuffle,
+                RequestKind::SoundSearch,
+            ],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "playlist",
+            description: "Build and play your own playlists.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![],
+            subcommands: vec![
+                RequestKind::PlaylistCreate,
+                RequestKind::PlaylistAdd,
+                RequestKind::PlaylistRemove,
+                RequestKind::PlaylistList,
+                RequestKind::PlaylistShow,
+                RequestKind::PlaylistDelete,
+                RequestKind::PlaylistPlay,
+                RequestKind::PlaylistExport,
+                RequestKind::PlaylistImport,
+            ],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "favorites",
+            description: "Play back what you liked.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![],
+            subcommands: vec![
+                RequestKind::FavoritesPlay,
+                RequestKind::FavoritesList,
+            ],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "record",
+            description: "Record what's said in a voice channel.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![],
+            subcommands: vec![
+                RequestKind::RecordStart,
+                RequestKind::RecordStop,
+                RequestKind::RecordOptOut,
+                RequestKind::RecordOptIn,
+            ],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "server",
+            description: "Server-wide settings, for server admins.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![],
+            subcommands: vec![
+                RequestKind::ServerClips,
+                RequestKind::ServerDucking,
+                RequestKind::ServerNowPlaying,
+            ],
+            opt_default_perm: None,
+        },
+        CommandTreeTop::Complex {
+            name: "voicestats",
+            description: "How long people speak in voice, for those who opted in.",
+            kind: CommandType::ChatInput,
+            subcommand_groups: vec![],
+            subcommands: vec![
+                RequestKind::VoiceStatsShow,
+                RequestKind::VoiceStatsOptIn,
+                RequestKind::VoiceStatsOptOut,
+                RequestKind::VoiceStatsPurge,
+            ],
+            opt_default_perm: None,
+        },
     ]
 }
@@ -1,15 +1,19 @@
-use std::{borrow::Cow, marker::PhantomData, path::{Path, PathBuf}};
+use std::{marker::PhantomData, path::{Path, PathBuf}, sync::Arc, time::Duration};
 use azel::discord::ExecutionContext;
-use songbird::input::cached::Memory;
+use songbird::Call;
+use tokio::sync::{watch, Mutex};
 use tracing as trc;
 
-use serenity::all::{ChannelId, CommandInteraction, Mention, ResolvedValue};
+use bigdecimal::ToPrimitive;
+use serenity::all::{ChannelId, CommandInteraction, EditInteractionResponse, GuildId, Http, Mention, ResolvedValue, UserId};
 use youtube_dl::YoutubeDl;
 
-use crate::audio::TrackErrorNotifier;
+use crate::{audio::{cached_input, new_track, register_track_events, TrackErrorNotifier, TrackInfo, TrackRetry}, cache::{self, CacheLease}, clip, db::{self, AudioLedgerEntry, metadata::{MediaMetadata, NewMediaMetadata, YOUTUBE_EXTRACTOR}}, download::{self, DownloadJob, DownloadStatus}, duck, limits::Limits, playback, voice_stats, ytdlp};
 
 use super::RequestError;
 
+const PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+
 #[derive(Debug)]
 pub struct Request<'a> {
     music: &'a str,
@@ -55,177 +59,386 @@ impl <'a> Request<'a> {
     pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
         let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
 
-        let target = if let Some(ch) = self.target {
-            let mut channels = guild_id.channels(ctx.ctx).await.map_err(|_e| RequestError::Internal("channel failed to load".into()))?;
-            channels.remove(&ch).ok_or_else(|| RequestError::User("channel not in guild".into()))?
-        } else {
-            ctx.find_interactor_voice_channel(guild_id).await?
-        };
+        let joined = join_for_playback(ctx, guild_id, self.target).await?;
 
-        let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
-        let mut join_required = false;
-        let handler = if let Some(handler) = manager.get(guild_id) {
-            handler
-        } else {
-            join_required = true;
-            match manager.join(guild_id, target.id).await {
-                Ok(handler) => handler,
-                Err(_e) => {
-                    return Err(RequestError::Internal("Voice channel join failed.".into()));
-                },
-            }
-        };
-
-        let mut handler_lock = handler.lock().await;
-
-        let current_joined_channel = handler_lock.current_channel();
-        let mut channel_changed_from = None;
-        if current_joined_channel != Some(target.id.into()) {
-            channel_changed_from = current_joined_channel;
-            match manager.join(guild_id, target.id).await {
-                Ok(_handler) => {},
-                Err(_e) => {
-                    return Err(RequestError::Internal("Voice channel join failed.".into()));
-                },
-            }
+        if is_youtube_url(self.music) {
+            // Metadata lookup can take a few seconds, downloads are run out of band.
+            ctx.defer().await?;
         }
+        let loaded = load_else_download(ctx, Playable::Music(self.music.to_owned())).await?;
+        let ready = ReadyAudio::prepare(loaded).await?;
 
-        let audio = match load_else_download(ctx, self.music).await? {
-            Ok(bytes) => songbird::input::Input::from(Memory::new(bytes.into()).await.unwrap()),
-            Err(live_play) => songbird::input::Input::from(live_play),
-        };
-
+        let mut handler_lock = joined.handler.lock().await;
         if self.clear_playlist {
+            playback::get(ctx.ctx).await.expect("playback manager initialized").end_session(guild_id);
             // Silently ignore if any errors.
             handler_lock.queue().stop();
         }
+        let (title, download) = enqueue(ctx, &mut handler_lock, ready).await?;
+        drop(handler_lock);
 
-        let track_handle = handler_lock.enqueue_input(audio).await;
-        track_handle.add_event(songbird::events::Event::Track(songbird::TrackEvent::Error), TrackErrorNotifier)
-            .map_err(|e| RequestError::Internal(format!("failure to set error handler {e:?}").into()))?;
+        let msg = joined.describe(title.as_str());
+        ctx.reply(msg.clone()).await?;
 
-        if let Some(ch) = channel_changed_from {
-            ctx.reply(format!("Switched to {} from {}!\nPlaying {}", Mention::Channel(target.id), Mention::Channel(ch.0.into()), self.music)).await?;
-        } else if join_required {
-            ctx.reply(format!("Joined channel {}!\nPlaying {}", Mention::Channel(target.id), self.music)).await?;
-        } else {
-            ctx.reply(format!("Queued {} for playback in {}!", self.music, Mention::Channel(target.id))).await?;
+        if let Some(download) = download {
+            tokio::spawn(report_download_progress(Arc::clone(&ctx.ctx.http), ctx.cmd.clone(), msg, download));
         }
 
         Ok(())
     }
 }
 
-// TODO lift ytdlp download to top later
-const YTDLP_DOWNLOAD_PATH: &str = "resources/bin/ytdlp";
-const YTDLP_EXEC_PATH: &str = constcat::concat!(YTDLP_DOWNLOAD_PATH, "/yt-dlp");
+/// The voice connection to play into, and how we got into that channel.
+pub struct Joined {
+    pub handler: Arc<Mutex<Call>>,
+    pub channel: ChannelId,
+    join_required: bool,
+    changed_from: Option<songbird::id::ChannelId>,
+}
 
-// TODO impl streaming properly instead of fully downloading first. Just don't play anything big
-async fn load_else_download(ctx: &ExecutionContext<'_>, music: &str) -> Result<Result<Vec<u8>, songbird::input::YoutubeDl<'static>>, RequestError> {
-    let load_path = if music.starts_with("https://www.youtube.com/watch") {
-        // We expect this will take a while
-        // TODO make this run out of band
-        ctx.defer().await?;
-
-        // pretend this always succeeds so that we can assume path exists later
-        std::fs::create_dir_all(YTDLP_DOWNLOAD_PATH).ok();
-        let mut contents = std::fs::read_dir(YTDLP_DOWNLOAD_PATH).map_err(|e| RequestError::Internal(format!("ytdlp check dir failed {e:?}").into()))?;
-        // assume non-empty dir has only a single file that is ytdlp
-        let ytdlp_path = match contents.next() {
-            Some(p) => {
-                trc::info!("YTDLP-LOAD-SKIP");
-                p.map_err(|e| RequestError::Internal(format!("ytdlp check failed {e:?}").into()))?.path()
+impl Joined {
+    /// Reply for having queued up `title`.
+    pub fn describe(&self, title: &str) -> String {
+        if let Some(ch) = self.changed_from {
+            format!("Switched to {} from {}!\nPlaying {title}", Mention::Channel(self.channel), Mention::Channel(ch.0.into()))
+        } else if self.join_required {
+            format!("Joined channel {}!\nPlaying {title}", Mention::Channel(self.channel))
+        } else {
+            format!("Queued {title} for playback in {}!", Mention::Channel(self.channel))
+        }
+    }
+}
+
+/// Joins `target`, or the requester's voice channel, moving over if already connected elsewhere in the guild.
+pub async fn join_for_playback(ctx: &ExecutionContext<'_>, guild_id: GuildId, target: Option<ChannelId>) -> Result<Joined, RequestError> {
+    let target = if let Some(ch) = target {
+        let mut channels = guild_id.channels(ctx.ctx).await.map_err(|_e| RequestError::Internal("channel failed to load".into()))?;
+        channels.remove(&ch).ok_or_else(|| RequestError::User("channel not in guild".into()))?
+    } else {
+        ctx.find_interactor_voice_channel(guild_id).await?
+    };
+
+    let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+    let mut join_required = false;
+    let handler = if let Some(handler) = manager.get(guild_id) {
+        handler
+    } else {
+        join_required = true;
+        match manager.join(guild_id, target.id).await {
+            Ok(handler) => handler,
+            Err(_e) => {
+                return Err(RequestError::Internal("Voice channel join failed.".into()));
             },
-            None => {
-                trc::info!("YTDLP-LOAD-START");
-                let p = youtube_dl::download_yt_dlp(YTDLP_DOWNLOAD_PATH).await.map_err(|_e| RequestError::Internal("file present".into()))?;
-                trc::info!("YTDLP-LOAD-END");
-                p
+        }
+    };
+
+    // Joining locks the call, so don't hold onto it here.
+    let current_joined_channel = handler.lock().await.current_channel();
+    let mut changed_from = None;
+    if current_joined_channel != Some(target.id.into()) {
+        changed_from = current_joined_channel;
+        match manager.join(guild_id, target.id).await {
+            Ok(_handler) => {},
+            Err(_e) => {
+                return Err(RequestError::Internal("Voice channel join failed.".into()));
             },
-        };
-
-        let mut yt_client = YoutubeDl::new(music.to_owned());
-        yt_client.youtube_dl_path(ytdlp_path.as_path());
-        yt_client.socket_timeout("15");
-        yt_client.output_directory("downloads");
-        yt_client.extra_arg("--no-playlist");
-
-        trc::info!("METADATA-LOAD-START");
-        let output = yt_client.clone()
-            .extra_arg("--skip-download")
-            .run_async().await
-            .map_err(|e| RequestError::Internal(format!("ytdlp failed {e:?}").into()))?
-            .into_single_video()
-            .ok_or_else(|| RequestError::User("bad input -- could not find video".into()))?;
-        trc::info!("METADATA-LOAD-END");
-        // TODO Set dl size limits
-        let video_download_dir = Path::new("downloads/yt").join(output.id);
-
-        // Best audio format, only
-        if !std::fs::exists(video_download_dir.as_path()).map_err(|_e| RequestError::Internal("vid dl check failure".into()))? {
-            trc::info!("LIVE-PLAY-REQUIRED");
-            // Download async in case we need to play this again later.
-            // It'd be nice if we could fork the inpu instead.... but
-            // that needs digging into how to do it which I don't have
-            // time for rght now.
-            tokio::spawn(async move {
-                trc::info!("VIDEO-DOWNLOAD-START");
-                // yt_client.format("a[acodec^=mp3]/ba/b");
-                // yt_client.extract_audio(true);
-                // yt_client.extra_arg("--audio-format").extra_arg("mp3");
-                yt_client.format("ba");
-
-                match yt_client.download_to_async(video_download_dir.clone()).await {
-                    Ok(_) => {},
-                    Err(e) => {
-                        trc::error!("ytdlp failed {e:?}");
-                        return;
-                    },
-                }
-                trc::info!("VIDEO-DOWNLOAD-END");
-            });
-            return Ok(Err(songbird::input::YoutubeDl::new_ytdl_like(YTDLP_EXEC_PATH, reqwest::Client::new(), music.to_owned())));
-        } else {
-            trc::info!("VIDEO-DOWNLOAD-SKIP");
+        }
+    }
+
+    if let Err(e) = clip::listen_if_enabled(ctx.ctx, ctx.db_cfg, guild_id, &handler).await {
+        // Playback doesn't need clips to work.
+        trc::warn!("CLIP-LISTEN-FAIL {guild_id} {e:?}");
+    }
+    if let Err(e) = duck::listen_if_enabled(ctx.ctx, ctx.db_cfg, guild_id, &handler).await {
+        trc::warn!("DUCK-LISTEN-FAIL {guild_id} {e:?}");
+    }
+    let voice_stats = voice_stats::get(ctx.ctx).await.expect("voice stats manager initialized");
+    voice_stats.listen(guild_id, &mut *handler.lock().await, db::share_config(ctx.db_cfg));
+
+    Ok(Joined {
+        handler,
+        channel: target.id,
+        join_required,
+        changed_from,
+    })
+}
+
+/// Loaded audio turned into an input the call can take right away. Cached files get read into memory here, which
+/// can take a while for big ones, so this is done before locking the call.
+pub struct ReadyAudio {
+    audio: songbird::input::Input,
+    info: TrackInfo,
+    lease: Option<CacheLease>,
+    download: Option<watch::Receiver<DownloadStatus>>,
+    retry: Option<TrackRetry>,
+}
+
+impl ReadyAudio {
+    pub async fn prepare((loaded, info): (LoadedAudio, TrackInfo)) -> Result<Self, RequestError> {
+        Ok(match loaded {
+            LoadedAudio::Cached { path, lease } => Self {
+                audio: cached_input(path).await?,
+                info,
+                lease,
+                download: None,
+                retry: None,
+            },
+            LoadedAudio::Live { stream, video_id, download } => Self {
+                audio: songbird::input::Input::from(*stream),
+                info,
+                lease: None,
+                retry: Some(TrackRetry::FromDownload { video_id, download: download.clone() }),
+                download: Some(download),
+            },
+        })
+    }
+}
+
+/// Adds prepared audio to the end of the call's queue. Returns the display title, and the background download to
+/// report on if there is one.
+pub async fn enqueue(ctx: &ExecutionContext<'_>, call: &mut Call, ready: ReadyAudio) -> Result<(String, Option<watch::Receiver<DownloadStatus>>), RequestError> {
+    let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+
+    let title = ready.info.display();
+    let track_handle = call.enqueue(new_track(ready.audio, ready.info)).await;
+    let notifier = TrackErrorNotifier::new(ctx, guild_id, ready.retry).await;
+    register_track_events(&track_handle, notifier, ready.lease)?;
+
+    Ok((title, ready.download))
+}
+
+/// Keeps the command's reply updated with how the background download is going, until it's done.
+pub async fn report_download_progress(http: Arc<Http>, interaction: CommandInteraction, base: String, mut progress: watch::Receiver<DownloadStatus>) {
+    loop {
+        let status = progress.borrow_and_update().clone();
+        let edit = EditInteractionResponse::new().content(format!("{base}\n-# {}", status.describe()));
+        if let Err(e) = interaction.edit_response(&*http, edit).await {
+            trc::warn!("download progress update failed {e:?}");
         }
 
-        let video_dl_contents = std::fs::read_dir(video_download_dir.as_path()).map_err(|_e| RequestError::Internal("video dl failure".into()))?;
-        let mut downloaded_vid_path = None;
-        // assume non-empty dir has only a single file that is video
-        for value in video_dl_contents {
-            downloaded_vid_path = Some(value.map_err(|_e| RequestError::Internal("dl files check failed".into()))?.path());
+        if status.is_done() || progress.changed().await.is_err() {
+            return;
         }
+        // Don't hammer discord with edits.
+        tokio::time::sleep(PROGRESS_UPDATE_INTERVAL).await;
+    }
+}
 
-        downloaded_vid_path.ok_or_else(|| RequestError::Internal("dl failed".into()))?
-    } else {
-        let path = if music.is_ascii() {
-            Cow::Borrowed(music)
+/// Something to play, either as typed into a command or already picked out of the ledger.
+pub enum Playable {
+    /// Youtube url or sound name.
+    Music(String),
+    Sound(Box<AudioLedgerEntry>),
+}
+
+impl Playable {
+    /// Ledger entries either are an uploaded sound, or remember a link to play.
+    pub fn from_ledger(entry: AudioLedgerEntry) -> Self {
+        if entry.downloaded {
+            Self::Sound(Box::new(entry))
         } else {
-            // Remove any path shenanigans so writes are isolated to this directory. We should do this
-            // in the OS too but I'm lazy.
-            Cow::Owned(music.chars().map(|c| if !c.is_ascii() || c == '/' || c == '\\' {
-                '_'
-            } else {
-                c
-            }).collect())
-        };
-        let path = &Path::new(path.as_ref());
-
-        // Cobbled together to hopefully make it work.
-        PathBuf::from("uploads").join(path).join("data.mp3")
+            Self::Music(entry.link_or_name)
+        }
+    }
+}
+
+pub enum LoadedAudio {
+    Cached {
+        path: PathBuf,
+        lease: Option<CacheLease>,
+    },
+    /// Not downloaded yet, so it's streamed while a download job saves it for next time.
+    Live {
+        stream: Box<songbird::input::YoutubeDl<'static>>,
+        video_id: String,
+        download: watch::Receiver<DownloadStatus>,
+    },
+}
+
+pub fn is_youtube_url(music: &str) -> bool {
+    music.starts_with("https://www.youtube.com/watch")
+}
+
+// TODO impl streaming properly instead of fully downloading first. Just don't play anything big
+/// Youtube urls can take a while, so callers should defer before loading one.
+pub async fn load_else_download(ctx: &ExecutionContext<'_>, playable: Playable) -> Result<(LoadedAudio, TrackInfo), RequestError> {
+    let sound = match playable {
+        Playable::Music(music) if is_youtube_url(music.as_str()) => return load_youtube(ctx, music.as_str()).await,
+        Playable::Music(name) => resolve_uploaded_sound(ctx, name.as_str()).await?,
+        Playable::Sound(sound) => *sound,
+    };
+
+    db::record_audio_play(ctx.db_cfg, sound.id).await?;
+    let info = TrackInfo {
+        title: sound.link_or_name.clone(),
+        source: sound.link_or_name.clone(),
+        duration_secs: sound.duration_secs,
+        thumbnail_url: None,
+        requester: ctx.cmd.user.id,
+        sound_id: Some(sound.id),
+    };
+    let load_path = PathBuf::from(sound.file_path);
+    check_file(load_path.as_path())?;
+
+    Ok((LoadedAudio::Cached {
+        path: load_path,
+        lease: None,
+    }, info))
+}
+
+async fn load_youtube(ctx: &ExecutionContext<'_>, music: &str) -> Result<(LoadedAudio, TrackInfo), RequestError> {
+    let cache = cache::get(ctx.ctx).await.expect("cache manager initialized");
+    let limits = Limits::for_guild(ctx.cmd.guild_id);
+
+    // Already downloaded videos have everything we need in the db, so skip asking ytdlp.
+    let known = match youtube_video_id(music) {
+        Some(video_id) => db::metadata::load_media_metadata(ctx.db_cfg, YOUTUBE_EXTRACTOR, video_id.as_str()).await?
+            .filter(|metadata| std::fs::exists(cache.entry_dir(metadata.video_id.as_str())).unwrap_or(false)),
+        None => None,
+    };
+    let metadata = match known {
+        Some(metadata) => {
+            trc::info!("METADATA-LOAD-SKIP {:?}", metadata.title);
+            metadata
+        },
+        None => fetch_metadata(ctx, music, &limits).await?,
     };
+    limits.check_duration(metadata.duration_secs)?;
+
+    let info = TrackInfo {
+        title: metadata.title.clone(),
+        source: music.to_owned(),
+        duration_secs: metadata.duration_secs,
+        thumbnail_url: metadata.thumbnail_url.clone(),
+        requester: ctx.cmd.user.id,
+        sound_id: None,
+    };
+
+    let video_download_dir = cache.entry_dir(metadata.video_id.as_str());
+
+    // Best audio format, only
+    if !std::fs::exists(video_download_dir.as_path()).map_err(|_e| RequestError::Internal("vid dl check failure".into()))? {
+        trc::info!("LIVE-PLAY-REQUIRED {:?}", metadata.title);
+        let ytdlp_path = ytdlp::get(ctx.ctx).await.expect("ytdlp manager initialized").ready_exec_path().await?;
+        // Download in the background in case we need to play this again later.
+        // It'd be nice if we could fork the input instead.... but
+        // that needs digging into how to do it which I don't have
+        // time for right now.
+        let downloads = download::get(ctx.ctx).await.expect("download manager initialized");
+        let download = downloads.submit(db::share_config(ctx.db_cfg), DownloadJob {
+            url: music.to_owned(),
+            video_id: metadata.video_id.clone(),
+            destination: video_download_dir,
+            max_filesize_bytes: limits.max_filesize_bytes,
+            ytdlp_path,
+        });
+        return Ok((LoadedAudio::Live {
+            stream: Box::new(songbird::input::YoutubeDl::new_ytdl_like(ytdlp_path, reqwest::Client::new(), music.to_owned())),
+            video_id: metadata.video_id.clone(),
+            download,
+        }, info));
+    } else {
+        trc::info!("VIDEO-DOWNLOAD-SKIP {:?}", metadata.title);
+    }
 
+    // Take the lease before touching the entry so the eviction pass can't pull the file out from under us.
+    let lease = cache.lease(metadata.video_id.as_str());
+    cache.mark_played(ctx.db_cfg, metadata.video_id.as_str()).await?;
+
+    let load_path = cache.downloaded_file(metadata.video_id.as_str())?;
+    check_file(load_path.as_path())?;
+
+    Ok((LoadedAudio::Cached {
+        path: load_path,
+        lease: Some(lease),
+    }, info))
+}
+
+fn check_file(load_path: &Path) -> Result<(), RequestError> {
     match load_path.canonicalize() {
         Ok(p) => {
             trc::info!("PLAY-FILE-LOAD {:?}", p);
+            Ok(())
         },
         Err(e) => {
-            trc::error!("PLAY-FILE-LOAD {:?} {:?} {e:?}", load_path.canonicalize(), load_path);
-            return Err(RequestError::User("You haven't uploaded this song or audio file yet! Please enter a youtube URL or upload a file.".into()));
+            trc::error!("PLAY-FILE-LOAD {:?} {e:?}", load_path);
+            Err(RequestError::Internal("The file for this sound has gone missing! Let the bot owner know.".into()))
         },
     }
-    trc::info!("PLAY-FILE-LOAD {:?} {:?}", load_path.canonicalize(), load_path);
-    let audio_file = std::fs::read(load_path).expect("file readable");
+}
+
+/// Finds an uploaded sound by name, looking at the requester's own uploads first, then ones shared with this guild,
+/// then public ones.
+pub async fn resolve_uploaded_sound(ctx: &ExecutionContext<'_>, name: &str) -> Result<AudioLedgerEntry, RequestError> {
+    let not_found = || RequestError::User("You haven't uploaded this song or audio file yet! Please enter a youtube URL or upload a file.".into());
+
+    if let Some(own) = db::load_maybe_known_audio_in_ledger(ctx.db_cfg, u64::from(ctx.cmd.user.id).into(), name).await? {
+        return Ok(own);
+    }
+
+    if let Some(guild_id) = ctx.cmd.guild_id {
+        if let Some(shared) = db::load_guild_audio_by_name(ctx.db_cfg, u64::from(guild_id).into(), name).await? {
+            return Ok(shared);
+        }
+        // Uploaded before sounds remembered their guild, these go by whether the uploader is around.
+        for sound in db::load_unscoped_audio_by_name(ctx.db_cfg, name).await? {
+            let Some(uploader) = sound.uploader.to_u64().filter(|id| *id != 0) else {
+                continue;
+            };
+            if guild_id.member(ctx.ctx, UserId::new(uploader)).await.is_ok() {
+                return Ok(sound);
+            }
+        }
+    }
+
+    db::load_public_audio_by_name(ctx.db_cfg, name).await?.ok_or_else(not_found)
+}
+
+/// Asks ytdlp about the video, checking it against the size limit and saving what we learn to the db.
+async fn fetch_metadata(ctx: &ExecutionContext<'_>, music: &str, limits: &Limits) -> Result<MediaMetadata, RequestError> {
+    let ytdlp_path = ytdlp::get(ctx.ctx).await.expect("ytdlp manager initialized").ready_exec_path().await?;
+
+    let mut yt_client = YoutubeDl::new(music.to_owned());
+    yt_client.youtube_dl_path(ytdlp_path);
+    yt_client.socket_timeout("15");
+    yt_client.extra_arg("--no-playlist");
+
+    trc::info!("METADATA-LOAD-START");
+    let output = yt_client
+        .extra_arg("--skip-download")
+        .run_async().await
+        .map_err(|e| RequestError::Internal(format!("ytdlp failed {e:?}").into()))?
+        .into_single_video()
+        .ok_or_else(|| RequestError::User("bad input -- could not find video".into()))?;
+    trc::info!("METADATA-LOAD-END {:?}", output.title);
 
-    Ok(Ok(audio_file))
+    limits.check_filesize(output.filesize.map(|s| s as u64).or(output.filesize_approx.map(|s| s as u64)))?;
+
+    let title = output.title.as_deref().unwrap_or(music);
+    let extractor = output.extractor.as_deref().unwrap_or(YOUTUBE_EXTRACTOR).to_ascii_lowercase();
+    // What was given may carry timestamps, playlist ids and such.
+    let url = output.webpage_url.clone().unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", output.id));
+    db::metadata::record_media_metadata(ctx.db_cfg, &NewMediaMetadata {
+        extractor: extractor.as_str(),
+        video_id: output.id.as_str(),
+        url: truncate(url.as_str(), 1024),
+        title: truncate(title, 1024),
+        uploader: output.uploader.as_deref().map(|uploader| truncate(uploader, 256)),
+        duration_secs: output.duration.as_ref().and_then(|d| d.as_f64()),
+        thumbnail_url: output.thumbnail.as_deref().filter(|url| url.len() <= 2048),
+    }).await
+}
+
+pub fn youtube_video_id(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let video_id = url.query_pairs().find(|(key, _)| key == "v")?.1;
+    Some(video_id.into_owned())
+}
+
+pub fn truncate(s: &str, max_bytes: usize) -> &str {
+    let mut end = s.len().min(max_bytes);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
 }
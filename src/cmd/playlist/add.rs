@@ -0,0 +1,54 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::playlists;
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    playlist: &'a str,
+    item: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut playlist = None;
+        let mut item = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "playlist" {
+                if let ResolvedValue::String(provided_playlist) = option.value {
+                    playlist = Some(provided_playlist);
+                }
+            }
+            if option.name == "music" {
+                if let ResolvedValue::String(provided_music) = option.value {
+                    item = Some(provided_music);
+                }
+            }
+        }
+
+        Ok(Self {
+            playlist: playlist.ok_or_else(|| RequestError::User("missing `playlist` required parameter".into()))?,
+            item,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Without `music`, adds whatever is playing right now.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let playlist = super::load_own_playlist(ctx, self.playlist).await?;
+
+        let audio = match self.item {
+            Some(item) => super::resolve_item(ctx, item).await?,
+            None => super::resolve_current_track(ctx).await?,
+        };
+        playlists::append_playlist_entries(ctx.db_cfg, playlist.id, &[audio.id]).await?;
+
+        ctx.reply_restricted(format!("Added {} to **{}**.", super::describe_entry(&audio), playlist.name)).await
+    }
+}
@@ -0,0 +1,51 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::playlists::{self, NewPlaylist};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name.trim());
+                }
+            }
+        }
+
+        let name = name.filter(|name| !name.is_empty()).ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?;
+        if name.chars().count() > super::MAX_NAME_LENGTH {
+            return Err(RequestError::User(format!("playlist names can be at most {} characters", super::MAX_NAME_LENGTH).into()));
+        }
+
+        Ok(Self {
+            name,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let created = playlists::create_playlist(ctx.db_cfg, &NewPlaylist {
+            discord_user: u64::from(ctx.cmd.user.id).into(),
+            name: self.name,
+        }).await?;
+
+        if created.is_none() {
+            ctx.reply_restricted(format!("You already have a playlist called **{}**.", self.name)).await?;
+            return Ok(());
+        }
+        ctx.reply_restricted(format!("Created playlist **{}**.", self.name)).await
+    }
+}
@@ -0,0 +1,41 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::playlists;
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    playlist: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut playlist = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "playlist" {
+                if let ResolvedValue::String(provided_playlist) = option.value {
+                    playlist = Some(provided_playlist);
+                }
+            }
+        }
+
+        Ok(Self {
+            playlist: playlist.ok_or_else(|| RequestError::User("missing `playlist` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Only the playlist goes, the sounds in it stay.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let playlist = super::load_own_playlist(ctx, self.playlist).await?;
+        playlists::delete_playlist(ctx.db_cfg, playlist.id).await?;
+
+        ctx.reply_restricted(format!("Deleted playlist **{}**.", playlist.name)).await
+    }
+}
@@ -0,0 +1,95 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, CreateAttachment, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedValue};
+
+use crate::db::{metadata::{self, YOUTUBE_EXTRACTOR}, playlists};
+
+use super::{format::{self, Format, PlaylistFile, PlaylistFileEntry, PLAYLIST_FILE_VERSION}, super::{play::youtube_video_id, resolve_subcommand, RequestError}};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    playlist: &'a str,
+    format: Format,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut playlist = None;
+        let mut format = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "playlist" {
+                if let ResolvedValue::String(provided_playlist) = option.value {
+                    playlist = Some(provided_playlist);
+                }
+            }
+            if option.name == "format" {
+                if let ResolvedValue::String(provided_format) = option.value {
+                    format = Some(Format::parse(provided_format).ok_or_else(|| {
+                        RequestError::User("`format` must be `json` or `m3u`".into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(Self {
+            playlist: playlist.ok_or_else(|| RequestError::User("missing `playlist` required parameter".into()))?,
+            format: format.unwrap_or(Format::Json),
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let playlist = super::load_own_playlist(ctx, self.playlist).await?;
+
+        let mut entries = vec![];
+        for (_, audio) in playlists::load_playlist_entries(ctx.db_cfg, playlist.id).await? {
+            if audio.downloaded {
+                entries.push(PlaylistFileEntry::Sound {
+                    name: audio.link_or_name,
+                    duration_secs: audio.duration_secs,
+                });
+                continue;
+            }
+
+            let known = match youtube_video_id(audio.link_or_name.as_str()) {
+                Some(video_id) => metadata::load_media_metadata(ctx.db_cfg, YOUTUBE_EXTRACTOR, video_id.as_str()).await?,
+                None => None,
+            };
+            entries.push(match known {
+                Some(known) => PlaylistFileEntry::Youtube {
+                    url: audio.link_or_name,
+                    title: Some(known.title),
+                    uploader: known.uploader,
+                    duration_secs: known.duration_secs,
+                    thumbnail_url: known.thumbnail_url,
+                },
+                None => PlaylistFileEntry::Youtube {
+                    url: audio.link_or_name,
+                    title: None,
+                    uploader: None,
+                    duration_secs: None,
+                    thumbnail_url: None,
+                },
+            });
+        }
+
+        let file = PlaylistFile {
+            version: PLAYLIST_FILE_VERSION,
+            name: playlist.name.clone(),
+            entries,
+        };
+        let contents = format::write(self.format, &file).map_err(|e| RequestError::Internal(e.into()))?;
+        let filename = format!("{}.{}", playlist.name.replace(['/', '\\'], "_"), self.format.extension());
+
+        let response = CreateInteractionResponseMessage::new()
+            .content(format!("Here's **{}**, with {} entries.", playlist.name, file.entries.len()))
+            .add_file(CreateAttachment::bytes(contents.into_bytes(), filename))
+            .ephemeral(true);
+        ctx.cmd.create_response(ctx.ctx, CreateInteractionResponse::Message(response)).await.map_err(|e| {
+            RequestError::Internal(format!("playlist upload to discord failed {e:?}").into())
+        })
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+/// Bump when changing [`PlaylistFile`] in a way older versions can't read.
+pub const PLAYLIST_FILE_VERSION: u32 = 1;
+
+/// Largest playlist file `/playlist import` will look at.
+pub const MAX_FILE_BYTES: u32 = 1024 * 1024;
+
+/// JSON form of a playlist, as written by `/playlist export`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaylistFile {
+    pub version: u32,
+    pub name: String,
+    pub entries: Vec<PlaylistFileEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PlaylistFileEntry {
+    /// Carries what we know about the video, so an import doesn't need to ask yt-dlp again.
+    Youtube {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uploader: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_secs: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        thumbnail_url: Option<String>,
+    },
+    /// Uploaded sound, by name. Resolved against whoever imports it.
+    Sound {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration_secs: Option<f64>,
+    },
+}
+
+impl PlaylistFileEntry {
+    /// What `/play` would take to play this.
+    pub fn item(&self) -> &str {
+        match self {
+            Self::Youtube { url, .. } => url.as_str(),
+            Self::Sound { name, .. } => name.as_str(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    M3u,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "m3u" | "m3u8" => Some(Self::M3u),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::M3u => "m3u",
+        }
+    }
+}
+
+pub fn write(format: Format, playlist: &PlaylistFile) -> Result<String, String> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(playlist).map_err(|e| format!("playlist serialization failed {e:?}")),
+        Format::M3u => Ok(write_m3u(playlist)),
+    }
+}
+
+fn write_m3u(playlist: &PlaylistFile) -> String {
+    let mut out = format!("#EXTM3U\n#PLAYLIST:{}\n", playlist.name);
+    for entry in playlist.entries.iter() {
+        let (title, duration) = match entry {
+            PlaylistFileEntry::Youtube { title, duration_secs, .. } => (title.as_deref(), *duration_secs),
+            PlaylistFileEntry::Sound { name, duration_secs } => (Some(name.as_str()), *duration_secs),
+        };
+        // Writing to a String can't fail.
+        let _ = writeln!(out, "#EXTINF:{},{}", duration.map(|d| d.round() as i64).unwrap_or(-1), title.unwrap_or(entry.item()));
+        let _ = writeln!(out, "{}", entry.item());
+    }
+    out
+}
+
+/// Reads either format. `filename` only serves as a hint, anything that parses as a playlist file is treated as one.
+pub fn read(filename: &str, data: &[u8]) -> Result<PlaylistFile, String> {
+    let text = std::str::from_utf8(data).map_err(|_e| "that file isn't text".to_owned())?;
+    let looks_like_json = filename.to_ascii_lowercase().ends_with(".json") || text.trim_start().starts_with('{');
+    if !looks_like_json {
+        return Ok(read_m3u(filename, text));
+    }
+
+    let playlist: PlaylistFile = serde_json::from_str(text).map_err(|e| format!("that isn't a playlist file ({e})"))?;
+    if playlist.version > PLAYLIST_FILE_VERSION {
+        return Err(format!("that playlist file is version {}, this bot only knows up to {PLAYLIST_FILE_VERSION}", playlist.version));
+    }
+    Ok(playlist)
+}
+
+/// Anything that isn't a comment is an entry. Durations in `#EXTINF` are dropped, since the files are often wrong.
+/// Plain `http://` links get upgraded, since youtube urls are only recognized over https.
+fn read_m3u(filename: &str, text: &str) -> PlaylistFile {
+    let mut name = filename.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(filename).to_owned();
+    let mut entries = vec![];
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(playlist_name) = line.strip_prefix("#PLAYLIST:") {
+            name = playlist_name.trim().to_owned();
+        } else if line.starts_with('#') {
+            continue;
+        } else if line.starts_with("https://") || line.starts_with("http://") {
+            entries.push(PlaylistFileEntry::Youtube {
+                url: line.strip_prefix("http://").map(|rest| format!("https://{rest}")).unwrap_or_else(|| line.to_owned()),
+                title: None,
+                uploader: None,
+                duration_secs: None,
+                thumbnail_url: None,
+            });
+        } else {
+            entries.push(PlaylistFileEntry::Sound {
+                name: line.to_owned(),
+                duration_secs: None,
+            });
+        }
+    }
+
+    PlaylistFile {
+        version: PLAYLIST_FILE_VERSION,
+        name,
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PlaylistFile {
+        PlaylistFile {
+            version: PLAYLIST_FILE_VERSION,
+            name: "road trip".to_owned(),
+            entries: vec![
+                PlaylistFileEntry::Youtube {
+                    url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned(),
+                    title: Some("Never Gonna Give You Up".to_owned()),
+                    uploader: Some("Rick Astley".to_owned()),
+                    duration_secs: Some(212.0),
+                    thumbnail_url: None,
+                },
+                PlaylistFileEntry::Sound {
+                    name: "airhorn".to_owned(),
+                    duration_secs: Some(1.5),
+                },
+            ],
+        }
+    }
+
+    fn items(playlist: &PlaylistFile) -> Vec<&str> {
+        playlist.entries.iter().map(PlaylistFileEntry::item).collect()
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let written = write(Format::Json, &sample()).unwrap();
+        let read_back = read("whatever.json", written.as_bytes()).unwrap();
+
+        assert_eq!(read_back.name, "road trip");
+        assert_eq!(items(&read_back), items(&sample()));
+        let PlaylistFileEntry::Youtube { title, uploader, duration_secs, .. } = &read_back.entries[0] else {
+            panic!("expected a youtube entry, got {:?}", read_back.entries[0]);
+        };
+        assert_eq!(title.as_deref(), Some("Never Gonna Give You Up"));
+        assert_eq!(uploader.as_deref(), Some("Rick Astley"));
+        assert_eq!(*duration_secs, Some(212.0));
+    }
+
+    #[test]
+    fn m3u_round_trips() {
+        let written = write(Format::M3u, &sample()).unwrap();
+        let read_back = read("whatever.m3u", written.as_bytes()).unwrap();
+
+        assert_eq!(read_back.name, "road trip");
+        assert_eq!(items(&read_back), items(&sample()));
+        assert!(matches!(read_back.entries[0], PlaylistFileEntry::Youtube { .. }));
+        assert!(matches!(read_back.entries[1], PlaylistFileEntry::Sound { .. }));
+    }
+
+    #[test]
+    fn m3u_takes_http_links() {
+        let read_back = read("mix.m3u", b"#EXTM3U\nhttp://www.youtube.com/watch?v=dQw4w9WgXcQ\nairhorn\n").unwrap();
+
+        assert_eq!(read_back.name, "mix");
+        assert_eq!(items(&read_back), vec!["https://www.youtube.com/watch?v=dQw4w9WgXcQ", "airhorn"]);
+    }
+
+    #[test]
+    fn newer_json_versions_are_refused() {
+        let mut playlist = sample();
+        playlist.version = PLAYLIST_FILE_VERSION + 1;
+        let written = write(Format::Json, &playlist).unwrap();
+
+        assert!(read("whatever.json", written.as_bytes()).is_err());
+    }
+}
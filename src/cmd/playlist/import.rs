@@ -0,0 +1,129 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{Attachment, CommandInteraction, ResolvedValue};
+use tracing as trc;
+
+use crate::{db::{metadata::{self, NewMediaMetadata, YOUTUBE_EXTRACTOR}, playlists::{self, NewPlaylist}}, limits::{shorten, with_list, LISTED_NAME_LENGTH}};
+
+use super::{format::{self, PlaylistFileEntry, MAX_FILE_BYTES}, super::{play::{truncate, youtube_video_id}, resolve_subcommand, RequestError}};
+
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    file: &'a Attachment,
+    name: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut file = None;
+        let mut name = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "file" {
+                if let ResolvedValue::Attachment(provided_file) = option.value {
+                    file = Some(provided_file);
+                }
+            }
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name.trim()).filter(|name| !name.is_empty());
+                }
+            }
+        }
+
+        Ok(Self {
+            file: file.ok_or_else(|| RequestError::User("missing `file` required parameter".into()))?,
+            name,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Creates a new playlist from a JSON or M3U file. Entries that don't resolve to a video or sound are left out
+    /// and reported.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        if self.file.size > MAX_FILE_BYTES {
+            return Err(RequestError::User("that file is too big to be a playlist".into()));
+        }
+        ctx.defer().await?;
+
+        let data = self.file.download().await.map_err(|_e| RequestError::Internal("Could not download file.".into()))?;
+        let file = format::read(self.file.filename.as_str(), data.as_slice()).map_err(|reason| RequestError::User(reason.into()))?;
+        if file.entries.len() > MAX_ENTRIES {
+            return Err(RequestError::User(format!("playlists can have at most {MAX_ENTRIES} entries").into()));
+        }
+
+        let name = self.name.unwrap_or(file.name.as_str());
+        if name.is_empty() || name.chars().count() > super::MAX_NAME_LENGTH {
+            return Err(RequestError::User(format!("playlist names must be between 1 and {} characters, pick one with `name`", super::MAX_NAME_LENGTH).into()));
+        }
+        let discord_user = u64::from(ctx.cmd.user.id);
+        let taken = || format!("You already have a playlist called **{name}**, pick another one with `name`.");
+        if playlists::load_playlist(ctx.db_cfg, discord_user.into(), name).await?.is_some() {
+            ctx.reply_restricted(taken()).await?;
+            return Ok(());
+        }
+
+        // Everything gets resolved first, so a failure partway through doesn't leave a half imported playlist.
+        let mut audio_ids = vec![];
+        let mut failed = vec![];
+        for entry in file.entries.iter() {
+            match super::resolve_item(ctx, entry.item()).await {
+                Ok(audio) => audio_ids.push(audio.id),
+                Err(e) => {
+                    trc::warn!("PLAYLIST-IMPORT-SKIP {:?} {e:?}", entry.item());
+                    failed.push(shorten(entry.item(), LISTED_NAME_LENGTH));
+                    continue;
+                },
+            }
+            remember_metadata(ctx, entry).await?;
+        }
+        if audio_ids.is_empty() {
+            let msg = format!("None of the {} entries could be found, so no playlist was made.", file.entries.len());
+            ctx.reply_restricted(with_list(msg, "Couldn't find:", failed)).await?;
+            return Ok(());
+        }
+
+        let Some(playlist) = playlists::create_playlist(ctx.db_cfg, &NewPlaylist {
+            discord_user: discord_user.into(),
+            name,
+        }).await? else {
+            ctx.reply_restricted(taken()).await?;
+            return Ok(());
+        };
+        playlists::append_playlist_entries(ctx.db_cfg, playlist.id, audio_ids.as_slice()).await?;
+
+        let mut msg = format!("Imported {} of {} entries into **{}**.", audio_ids.len(), file.entries.len(), playlist.name);
+        if !failed.is_empty() {
+            msg = with_list(msg, "Couldn't find:", failed);
+        }
+        ctx.reply_restricted(msg).await
+    }
+}
+
+/// Saves video details that came with the file, unless we already know better.
+async fn remember_metadata(ctx: &ExecutionContext<'_>, entry: &PlaylistFileEntry) -> Result<(), RequestError> {
+    let PlaylistFileEntry::Youtube { url, title: Some(title), uploader, duration_secs, thumbnail_url } = entry else {
+        return Ok(());
+    };
+    let Some(video_id) = youtube_video_id(url.as_str()) else {
+        return Ok(());
+    };
+    if metadata::load_media_metadata(ctx.db_cfg, YOUTUBE_EXTRACTOR, video_id.as_str()).await?.is_some() {
+        return Ok(());
+    }
+
+    metadata::record_media_metadata(ctx.db_cfg, &NewMediaMetadata {
+        extractor: YOUTUBE_EXTRACTOR,
+        video_id: truncate(video_id.as_str(), 64),
+        url: truncate(url.as_str(), 1024),
+        title: truncate(title.as_str(), 1024),
+        uploader: uploader.as_deref().map(|uploader| truncate(uploader, 256)),
+        duration_secs: *duration_secs,
+        thumbnail_url: thumbnail_url.as_deref().filter(|url| url.len() <= 2048),
+    }).await?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::{db::playlists, limits::{fit_lines, shorten, LISTED_NAME_LENGTH, MESSAGE_LENGTH}};
+
+use super::super::RequestError;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let owned = playlists::load_playlists(ctx.db_cfg, u64::from(ctx.cmd.user.id).into()).await?;
+        if owned.is_empty() {
+            ctx.reply_restricted("You don't have any playlists yet, make one with `/playlist create`.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let header = "Your playlists:\n";
+        let lines = owned.iter()
+            .map(|(playlist, count)| format!("- **{}** ({count} entries)", shorten(&playlist.name, LISTED_NAME_LENGTH)))
+            .collect();
+        let msg = format!("{header}{}", fit_lines(lines, owned.len(), MESSAGE_LENGTH - header.chars().count()));
+
+        ctx.reply_restricted(msg).await
+    }
+}
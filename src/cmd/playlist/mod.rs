@@ -0,0 +1,218 @@
+pub mod create;
+pub mod add;
+pub mod remove;
+pub mod list;
+pub mod show;
+pub mod delete;
+pub mod play;
+pub mod export;
+pub mod import;
+pub mod format;
+
+use std::collections::HashMap;
+
+use azel::discord::ExecutionContext;
+use bigdecimal::BigDecimal;
+use rand::{seq::SliceRandom, Rng};
+use serenity::all::{ChannelId, GuildId};
+use tracing as trc;
+
+use crate::{audio::track_info, cache, limits::{shorten, with_list, LISTED_NAME_LENGTH}, playback, db::{self, playlists::{self, Playlist}, ratings, AudioLedgerEntry, NewAudioLedgerEntry, Visibility}};
+
+use super::{play::{enqueue, is_youtube_url, join_for_playback, load_else_download, resolve_uploaded_sound, youtube_video_id, Playable, ReadyAudio}, RequestError};
+
+/// Matches the `playlists.name` column.
+pub const MAX_NAME_LENGTH: usize = 100;
+/// What smart shuffling assumes listeners think of tracks they haven't rated.
+const NEUTRAL_RATING: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Listed,
+    Shuffled,
+    /// Shuffled, but favoring tracks the people in the voice channel rated highly.
+    Smart,
+}
+
+impl Order {
+    pub fn from_flags(shuffle: bool, smart: bool) -> Self {
+        match (shuffle, smart) {
+            (_, true) => Order::Smart,
+            (true, false) => Order::Shuffled,
+            (false, false) => Order::Listed,
+        }
+    }
+}
+
+/// Playlists are per user, so this only ever finds the requester's own.
+pub async fn load_own_playlist(ctx: &ExecutionContext<'_>, name: &str) -> Result<Playlist, RequestError> {
+    playlists::load_playlist(ctx.db_cfg, u64::from(ctx.cmd.user.id).into(), name).await?
+        .ok_or_else(|| RequestError::User(format!("You don't have a playlist called **{name}**.").into()))
+}
+
+/// Turns a youtube url or sound name into the ledger entry a playlist can point at. Urls get a link entry for the
+/// requester, sound names are looked up the same way `/play` does.
+pub async fn resolve_item(ctx: &ExecutionContext<'_>, item: &str) -> Result<AudioLedgerEntry, RequestError> {
+    if !is_youtube_url(item) {
+        return resolve_uploaded_sound(ctx, item).await;
+    }
+
+    let video_id = youtube_video_id(item).ok_or_else(|| RequestError::User("that youtube url doesn't point at a video".into()))?;
+    // Drop timestamps, playlist ids and such, so the same video always maps to the same entry.
+    let url = format!("https://www.youtube.com/watch?v={video_id}");
+    let cache = cache::get(ctx.ctx).await.expect("cache manager initialized");
+    db::track_link_in_ledger(ctx.db_cfg, &NewAudioLedgerEntry {
+        link_or_name: url.as_str(),
+        downloaded: false,
+        file_path: cache.entry_dir(video_id.as_str()).to_string_lossy().into_owned(),
+        uploader: u64::from(ctx.cmd.user.id).into(),
+        codec: None,
+        sample_rate: None,
+        channels: None,
+        duration_secs: None,
+        original_file_path: None,
+        blob_hash: None,
+        guild_id: None,
+        visibility: Visibility::Private.as_str(),
+    }).await
+}
+
+/// Ledger entry for whatever is playing right now, so commands can default to it.
+pub async fn resolve_current_track(ctx: &ExecutionContext<'_>) -> Result<AudioLedgerEntry, RequestError> {
+    let nothing_playing = || RequestError::User("nothing is playing, so give a url or sound name".into());
+
+    let guild_id = ctx.cmd.guild_id.ok_or_else(nothing_playing)?;
+    let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+    let handler = manager.get(guild_id).ok_or_else(nothing_playing)?;
+    let current = handler.lock().await.queue().current().ok_or_else(nothing_playing)?;
+    let info = track_info(&current);
+
+    // Sounds can share names, so go by what was actually queued.
+    if let Some(sound_id) = info.sound_id {
+        if let Some(sound) = db::load_audio(ctx.db_cfg, sound_id).await? {
+            return Ok(sound);
+        }
+    }
+    resolve_item(ctx, info.source.as_str()).await
+}
+
+/// Sound name or link, for listing entries in chat.
+pub fn describe_entry(audio: &AudioLedgerEntry) -> String {
+    if audio.downloaded {
+        format!("**{}**", shorten(&audio.link_or_name, LISTED_NAME_LENGTH))
+    } else {
+        format!("<{}>", shorten(&audio.link_or_name, LISTED_NAME_LENGTH))
+    }
+}
+
+/// Queues every entry after whatever's already queued, or instead of it with `replace`. Entries that fail to load
+/// are skipped and reported.
+pub async fn queue_playlist(ctx: &ExecutionContext<'_>, playlist: &Playlist, order: Order, target: Option<ChannelId>, replace: bool) -> Result<(), RequestError> {
+    let entries = playlists::load_playlist_entries(ctx.db_cfg, playlist.id).await?;
+    if entries.is_empty() {
+        ctx.reply_restricted(format!("**{}** is empty, add to it with `/playlist add`.", playlist.name)).await?;
+        return Ok(());
+    }
+
+    let entries = entries.into_iter().map(|(_, audio)| audio).collect();
+    queue_entries(ctx, format!("**{}**", playlist.name).as_str(), entries, order, None, target, replace).await
+}
+
+/// Queues `entries` in the given order, stopping after `limit` of them. `label` is what the reply says they're from.
+pub async fn queue_entries(
+    ctx: &ExecutionContext<'_>,
+    label: &str,
+    mut entries: Vec<AudioLedgerEntry>,
+    order: Order,
+    limit: Option<usize>,
+    target: Option<ChannelId>,
+    replace: bool,
+) -> Result<(), RequestError> {
+    let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+
+    // Youtube entries might need a metadata lookup each.
+    ctx.defer().await?;
+    let joined = join_for_playback(ctx, guild_id, target).await?;
+    match order {
+        Order::Listed => {},
+        Order::Shuffled => entries.shuffle(&mut rand::thread_rng()),
+        Order::Smart => entries = smart_shuffle(ctx, guild_id, joined.channel, entries).await?,
+    }
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+    // Replacing only happens once something has loaded, so a playlist that can't load leaves the queue alone.
+    let mut replace = replace;
+    let mut queued = 0;
+    let mut failed = vec![];
+    for audio in entries {
+        let description = describe_entry(&audio);
+        let prepared = match load_else_download(ctx, Playable::from_ledger(audio)).await {
+            Ok(loaded) => ReadyAudio::prepare(loaded).await,
+            Err(e) => Err(e),
+        };
+        let ready = match prepared {
+            Ok(ready) => ready,
+            Err(e) => {
+                trc::warn!("PLAYLIST-ENTRY-LOAD-FAIL {:?} {e:?}", description);
+                failed.push(description);
+                continue;
+            },
+        };
+        let mut handler = joined.handler.lock().await;
+        if replace {
+            playback::get(ctx.ctx).await.expect("playback manager initialized").end_session(guild_id);
+            // Silently ignore if any errors.
+            handler.queue().stop();
+            replace = false;
+        }
+        enqueue(ctx, &mut handler, ready).await?;
+        queued += 1;
+    }
+
+    let mut msg = joined.describe(format!("{queued} track(s) from {label}").as_str());
+    if !failed.is_empty() {
+        msg = with_list(msg, "Couldn't load:", failed);
+    }
+    ctx.reply(msg).await
+}
+
+/// Weighted shuffle, where a track's weight is the square of its average rating among everyone in `channel`. Tracks
+/// nobody there rated count as [`NEUTRAL_RATING`].
+async fn smart_shuffle(ctx: &ExecutionContext<'_>, guild_id: GuildId, channel: ChannelId, entries: Vec<AudioLedgerEntry>) -> Result<Vec<AudioLedgerEntry>, RequestError> {
+    let bot = ctx.ctx.cache.current_user().id;
+    let listeners: Vec<BigDecimal> = ctx.ctx.cache.guild(guild_id)
+        .map(|guild| guild.voice_states.values()
+            .filter(|state| state.channel_id == Some(channel) && state.user_id != bot)
+            .map(|state| u64::from(state.user_id).into())
+            .collect())
+        .unwrap_or_default();
+
+    let mut rated: HashMap<i64, (f64, usize)> = HashMap::new();
+    if !listeners.is_empty() {
+        for (_, audio, rating) in ratings::load_ratings(ctx.db_cfg, &listeners, &entries).await? {
+            let (total, raters) = rated.entry(audio).or_default();
+            *total += f64::from(rating);
+            *raters += 1;
+        }
+    }
+
+    // Efraimidis-Spirakis: sorting by u^(1/weight) is the same as repeatedly drawing without replacement.
+    let mut rng = rand::thread_rng();
+    let mut keyed: Vec<_> = entries.into_iter()
+        .map(|audio| {
+            let (total, raters) = rated.get(&audio.id).copied().unwrap_or_default();
+            let unrated = listeners.len().saturating_sub(raters);
+            let average = if listeners.is_empty() {
+                NEUTRAL_RATING
+            } else {
+                (total + NEUTRAL_RATING * unrated as f64) / listeners.len() as f64
+            };
+            let key = rng.gen::<f64>().powf(1.0 / (average * average));
+            (key, audio)
+        })
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    Ok(keyed.into_iter().map(|(_, audio)| audio).collect())
+}
@@ -0,0 +1,60 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{ChannelId, CommandInteraction, ResolvedValue};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    playlist: &'a str,
+    shuffle: bool,
+    smart: bool,
+    target: Option<ChannelId>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut playlist = None;
+        let mut shuffle = false;
+        let mut smart = false;
+        let mut target = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "playlist" {
+                if let ResolvedValue::String(provided_playlist) = option.value {
+                    playlist = Some(provided_playlist);
+                }
+            }
+            if option.name == "shuffle" {
+                if let ResolvedValue::Boolean(provided_shuffle) = option.value {
+                    shuffle = provided_shuffle;
+                }
+            }
+            if option.name == "smart" {
+                if let ResolvedValue::Boolean(provided_smart) = option.value {
+                    smart = provided_smart;
+                }
+            }
+            if option.name == "target" {
+                if let ResolvedValue::Channel(target_channel) = option.value {
+                    target = Some(target_channel.id);
+                }
+            }
+        }
+
+        Ok(Self {
+            playlist: playlist.ok_or_else(|| RequestError::User("missing `playlist` required parameter".into()))?,
+            shuffle,
+            smart,
+            target,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let playlist = super::load_own_playlist(ctx, self.playlist).await?;
+        super::queue_playlist(ctx, &playlist, super::Order::from_flags(self.shuffle, self.smart), self.target, false).await
+    }
+}
@@ -0,0 +1,57 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::playlists;
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    playlist: &'a str,
+    position: usize,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut playlist = None;
+        let mut position = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "playlist" {
+                if let ResolvedValue::String(provided_playlist) = option.value {
+                    playlist = Some(provided_playlist);
+                }
+            }
+            if option.name == "position" {
+                if let ResolvedValue::Integer(provided_position) = option.value {
+                    position = Some(usize::try_from(provided_position).ok().filter(|position| *position > 0).ok_or_else(|| {
+                        RequestError::User("`position` starts at 1".into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(Self {
+            playlist: playlist.ok_or_else(|| RequestError::User("missing `playlist` required parameter".into()))?,
+            position: position.ok_or_else(|| RequestError::User("missing `position` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// `position` is as numbered by `/playlist show`.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let playlist = super::load_own_playlist(ctx, self.playlist).await?;
+
+        let entries = playlists::load_playlist_entries(ctx.db_cfg, playlist.id).await?;
+        let Some((entry, audio)) = entries.get(self.position - 1) else {
+            ctx.reply_restricted(format!("**{}** only has {} entries.", playlist.name, entries.len())).await?;
+            return Ok(());
+        };
+        playlists::remove_playlist_entry(ctx.db_cfg, entry).await?;
+
+        ctx.reply_restricted(format!("Removed {} from **{}**.", super::describe_entry(audio), playlist.name)).await
+    }
+}
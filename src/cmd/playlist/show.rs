@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::{db::playlists, limits::{fit_lines, shorten, LISTED_NAME_LENGTH, MESSAGE_LENGTH}};
+
+use super::super::{resolve_subcommand, RequestError};
+
+const LISTED_ENTRIES: usize = 25;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    playlist: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut playlist = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "playlist" {
+                if let ResolvedValue::String(provided_playlist) = option.value {
+                    playlist = Some(provided_playlist);
+                }
+            }
+        }
+
+        Ok(Self {
+            playlist: playlist.ok_or_else(|| RequestError::User("missing `playlist` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let playlist = super::load_own_playlist(ctx, self.playlist).await?;
+
+        let entries = playlists::load_playlist_entries(ctx.db_cfg, playlist.id).await?;
+        if entries.is_empty() {
+            ctx.reply_restricted(format!("**{}** is empty, add to it with `/playlist add`.", playlist.name)).await?;
+            return Ok(());
+        }
+
+        let header = format!("**{}**:\n", shorten(&playlist.name, LISTED_NAME_LENGTH));
+        let lines = entries.iter().take(LISTED_ENTRIES).enumerate()
+            .map(|(idx, (_, audio))| format!("{}. {}", idx + 1, super::describe_entry(audio)))
+            .collect();
+        let msg = format!("{header}{}", fit_lines(lines, entries.len(), MESSAGE_LENGTH - header.chars().count()));
+
+        ctx.reply_restricted(msg).await
+    }
+}
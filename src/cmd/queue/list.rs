@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, Mention};
+
+use crate::{audio::track_info, limits::{fit_lines, MESSAGE_LENGTH}};
+
+use super::super::RequestError;
+
+const LISTED_TRACKS: usize = 20;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+
+        let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+        let Some(handler) = manager.get(guild_id) else {
+            ctx.reply_restricted("Not currently playing, nothing queued.".to_owned()).await?;
+            return Ok(());
+        };
+
+        let tracks = handler.lock().await.queue().current_queue();
+        if tracks.is_empty() {
+            ctx.reply_restricted("Nothing queued.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let lines = tracks.iter().take(LISTED_TRACKS).enumerate()
+            .map(|(idx, track)| {
+                let info = track_info(track);
+                let line = format!("{} -- requested by {}", info.display(), Mention::User(info.requester));
+                if idx == 0 {
+                    format!("Now playing: {line}")
+                } else {
+                    format!("{idx}. {line}")
+                }
+            })
+            .collect();
+
+        ctx.reply(fit_lines(lines, tracks.len(), MESSAGE_LENGTH)).await
+    }
+}
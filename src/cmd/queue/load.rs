@@ -0,0 +1,44 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use super::super::{playlist, resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    replace: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+        let mut replace = false;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+            if option.name == "replace" {
+                if let ResolvedValue::Boolean(provided_replace) = option.value {
+                    replace = provided_replace;
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            replace,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let saved = playlist::load_own_playlist(ctx, self.name).await?;
+        playlist::queue_playlist(ctx, &saved, playlist::Order::Listed, None, self.replace).await
+    }
+}
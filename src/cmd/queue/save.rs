@@ -0,0 +1,96 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+use tracing as trc;
+
+use crate::{audio::track_info, db::playlists::{self, NewPlaylist}, limits::with_list};
+
+use super::super::{playlist, resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    overwrite: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+        let mut overwrite = false;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name.trim());
+                }
+            }
+            if option.name == "overwrite" {
+                if let ResolvedValue::Boolean(provided_overwrite) = option.value {
+                    overwrite = provided_overwrite;
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.filter(|name| !name.is_empty()).ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            overwrite,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Saves the queue, including the track that's playing, as one of the requester's playlists.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+
+        let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+        let tracks = match manager.get(guild_id) {
+            Some(handler) => handler.lock().await.queue().current_queue(),
+            None => vec![],
+        };
+        if tracks.is_empty() {
+            ctx.reply_restricted("Nothing queued to save.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let user_id = u64::from(ctx.cmd.user.id);
+        let existing = playlists::load_playlist(ctx.db_cfg, user_id.into(), self.name).await?;
+        if existing.is_some() && !self.overwrite {
+            ctx.reply_restricted(format!("You already have a playlist called **{}**, pass `overwrite` to replace it.", self.name)).await?;
+            return Ok(());
+        }
+
+        let mut audio_ids = vec![];
+        let mut skipped = vec![];
+        for track in tracks.iter() {
+            let info = track_info(track);
+            if let Some(sound_id) = info.sound_id {
+                audio_ids.push(sound_id);
+                continue;
+            }
+            match playlist::resolve_item(ctx, info.source.as_str()).await {
+                Ok(audio) => audio_ids.push(audio.id),
+                Err(e) => {
+                    trc::warn!("QUEUE-SAVE-SKIP {:?} {e:?}", info.source);
+                    skipped.push(info.display());
+                },
+            }
+        }
+
+        let saved = match existing {
+            Some(saved) => saved,
+            None => playlists::create_playlist(ctx.db_cfg, &NewPlaylist {
+                discord_user: user_id.into(),
+                name: self.name,
+            }).await?.ok_or_else(|| RequestError::User("Database insert failed".into()))?,
+        };
+        playlists::replace_playlist_entries(ctx.db_cfg, saved.id, audio_ids.as_slice()).await?;
+
+        let mut msg = format!("Saved {} track(s) to **{}**.", audio_ids.len(), saved.name);
+        if !skipped.is_empty() {
+            msg = with_list(msg, "Couldn't save:", skipped);
+        }
+        ctx.reply_restricted(msg).await
+    }
+}
@@ -0,0 +1,62 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::ratings::{self, NewTrackRating};
+
+use super::{playlist, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    stars: i16,
+    music: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut stars = None;
+        let mut music = None;
+
+        for option in cmd.data.options().iter() {
+            if option.name == "stars" {
+                if let ResolvedValue::Integer(provided_stars) = option.value {
+                    stars = Some(i16::try_from(provided_stars).ok().filter(|stars| (1..=5).contains(stars)).ok_or_else(|| {
+                        RequestError::User("`stars` goes from 1 to 5".into())
+                    })?);
+                }
+            }
+            if option.name == "music" {
+                if let ResolvedValue::String(provided_music) = option.value {
+                    music = Some(provided_music);
+                }
+            }
+        }
+
+        Ok(Self {
+            stars: stars.ok_or_else(|| RequestError::User("missing `stars` required parameter".into()))?,
+            music,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        rate_track(ctx, self.music, self.stars).await
+    }
+}
+
+/// Without `music`, rates whatever is playing right now.
+pub async fn rate_track(ctx: &ExecutionContext<'_>, music: Option<&str>, stars: i16) -> Result<(), RequestError> {
+    let audio = match music {
+        Some(music) => playlist::resolve_item(ctx, music).await?,
+        None => playlist::resolve_current_track(ctx).await?,
+    };
+    ratings::rate_audio(ctx.db_cfg, &NewTrackRating {
+        discord_user: u64::from(ctx.cmd.user.id).into(),
+        audio: audio.id,
+        rating: stars,
+    }).await?;
+
+    ctx.reply_restricted(format!("Rated {} {}.", playlist::describe_entry(&audio), "★".repeat(usize::from(stars.unsigned_abs())))).await
+}
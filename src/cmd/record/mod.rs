@@ -0,0 +1,51 @@
+pub mod start;
+pub mod stop;
+pub mod opt_out;
+
+use azel::discord::ExecutionContext;
+
+use crate::{db, probe, storage::{self, StoredSound}};
+
+use super::{play::is_youtube_url, RequestError};
+
+/// Matches the `audio_ledger.link_or_name` column.
+const MAX_SOUND_NAME_LENGTH: usize = 1024;
+
+/// Checks `name` is free among the requester's sounds and something `/play` would look up as a sound. Meant for
+/// before the audio is on the line, so a bad name doesn't cost it.
+pub async fn check_sound_name(ctx: &ExecutionContext<'_>, name: &str) -> Result<(), RequestError> {
+    if name.is_empty() || name.chars().count() > MAX_SOUND_NAME_LENGTH {
+        return Err(RequestError::User(format!("sound names must be between 1 and {MAX_SOUND_NAME_LENGTH} characters").into()));
+    }
+    if is_youtube_url(name) {
+        return Err(RequestError::User("that's a youtube url, `/play` couldn't find a sound called that".into()));
+    }
+    if db::load_maybe_known_audio_in_ledger(ctx.db_cfg, u64::from(ctx.cmd.user.id).into(), name).await?.is_some() {
+        return Err(RequestError::User(format!("You already have a sound called **{name}**.").into()));
+    }
+    Ok(())
+}
+
+/// Adds recorded audio to the sound library, like `/upload` would. Returns a line saying how to play it.
+pub async fn save_as_sound(ctx: &ExecutionContext<'_>, name: &str, filename: &'static str, wav: Vec<u8>) -> Result<String, RequestError> {
+    let probe = tokio::task::spawn_blocking({
+        let data = wav.clone();
+        move || probe::probe_audio(filename, data)
+    }).await
+        .map_err(|e| RequestError::Internal(format!("audio probe failed {e:?}").into()))?
+        .map_err(|reason| RequestError::Internal(reason.into()))?;
+
+    let stored = storage::store_sound(ctx.db_cfg, storage::NewSound {
+        name,
+        filename,
+        data: wav.as_slice(),
+        probe,
+        uploader: u64::from(ctx.cmd.user.id).into(),
+        guild_id: ctx.cmd.guild_id.map(|guild_id| u64::from(guild_id).into()),
+    }).await?;
+
+    Ok(match stored {
+        StoredSound::Duplicate(existing) => format!("Saved as **{name}** (same audio as **{existing}**)."),
+        StoredSound::New(_) => format!("Saved as **{name}**, play it with `/play {name}`."),
+    })
+}
@@ -0,0 +1,36 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::db::recording;
+
+use super::super::RequestError;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    opt_out: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction, opt_out: bool) -> Result<Self, RequestError> {
+        Ok(Self {
+            opt_out,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Applies everywhere, and to recordings already running once they're stopped.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let user_id = u64::from(ctx.cmd.user.id).into();
+        if self.opt_out {
+            recording::opt_out_of_recording(ctx.db_cfg, user_id).await?;
+            ctx.reply_restricted("You won't be in any recordings from now on.".to_owned()).await
+        } else if recording::opt_in_to_recording(ctx.db_cfg, user_id).await? {
+            ctx.reply_restricted("You'll be in recordings again.".to_owned()).await
+        } else {
+            ctx.reply_restricted("You weren't opted out.".to_owned()).await
+        }
+    }
+}
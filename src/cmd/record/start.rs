@@ -0,0 +1,64 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use azel::discord::ExecutionContext;
+use serenity::all::{ChannelId, CommandInteraction, Mention, ResolvedValue};
+use songbird::CoreEvent;
+use tracing as trc;
+
+use crate::{db, record::{self, Recording, RecordingReceiver}, settings};
+
+use super::super::{play::join_for_playback, resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    target: Option<ChannelId>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut target = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "target" {
+                if let ResolvedValue::Channel(target_channel) = option.value {
+                    target = Some(target_channel.id);
+                }
+            }
+        }
+
+        Ok(Self {
+            target,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+        let recordings = record::get(ctx.ctx).await.expect("recording manager initialized");
+
+        let joined = join_for_playback(ctx, guild_id, self.target).await?;
+        let max_duration_secs = settings::get().recording.max_duration_secs.min(record::MAX_POSTABLE_SECS);
+        let recording = Arc::new(Recording::new(joined.channel, db::share_config(ctx.db_cfg), max_duration_secs));
+        if !recordings.begin(guild_id, Arc::clone(&recording)) {
+            return Err(RequestError::User("already recording here, `/record stop` first".into()));
+        }
+
+        {
+            let mut call = joined.handler.lock().await;
+            record::set_decoding(&mut call, true);
+            let receiver = RecordingReceiver(recording);
+            call.add_global_event(CoreEvent::SpeakingStateUpdate.into(), receiver.clone());
+            call.add_global_event(CoreEvent::VoiceTick.into(), receiver);
+        }
+        trc::info!("RECORD-START {guild_id} {}", joined.channel);
+
+        // Everyone in the channel should know, so this isn't restricted.
+        ctx.reply(format!(
+            "🔴 **Recording {} now**, for up to {} minute(s) or until `/record stop`. \
+            Don't want to be in it? `/record optout` and you'll be left out.",
+            Mention::Channel(joined.channel),
+            max_duration_secs / 60,
+        )).await
+    }
+}
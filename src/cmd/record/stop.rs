@@ -0,0 +1,102 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use serenity::all::{CommandInteraction, CreateAttachment, EditInteractionResponse, Mention, ResolvedValue, UserId};
+use tracing as trc;
+
+use crate::{clip, db::recording, limits::format_duration, record};
+
+use super::super::{resolve_subcommand, RequestError};
+
+const RECORDING_FILENAME: &str = "recording.wav";
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    save_as: Option<&'a str>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut save_as = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "save_as" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    save_as = Some(provided_name.trim());
+                }
+            }
+        }
+
+        Ok(Self {
+            save_as,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Posts the recording, and with `save_as` also adds it to the sound library. Should either fail, the recording
+    /// is kept so stopping again retries. A save that already went through isn't repeated.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+        let recordings = record::get(ctx.ctx).await.expect("recording manager initialized");
+        if let Some(name) = self.save_as {
+            let already_saved = recordings.current(guild_id).is_some_and(|recording| recording.saved_as(name).is_some());
+            if !already_saved {
+                super::check_sound_name(ctx, name).await?;
+            }
+        }
+
+        let Some(recording) = recordings.stop(guild_id) else {
+            return Err(RequestError::User("not recording here".into()));
+        };
+        trc::info!("RECORD-STOP {guild_id} {}", recording.channel);
+        let clips = clip::get(ctx.ctx).await.expect("clip manager initialized");
+        let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+        if let Some(handler) = manager.get(guild_id).filter(|_| !clips.is_listening(guild_id)) {
+            record::set_decoding(&mut *handler.lock().await, false);
+        }
+
+        ctx.defer().await?;
+
+        // Someone might have opted out after they started talking.
+        let mut voices = recording.voices();
+        let speakers: Vec<_> = voices.keys().map(|user_id| u64::from(*user_id).into()).collect();
+        for opted_out in recording::load_opted_out(ctx.db_cfg, &speakers).await? {
+            if let Some(user_id) = opted_out.to_u64() {
+                voices.remove(&UserId::new(user_id));
+            }
+        }
+        if voices.is_empty() {
+            recordings.finish(guild_id);
+            ctx.reply(format!("Stopped recording {}. Nobody said anything, so there's nothing to post.", Mention::Channel(recording.channel))).await?;
+            return Ok(());
+        }
+
+        let wav = record::encode_wav(&record::mix(voices.values()));
+        let mut msg = format!(
+            "Stopped recording {}, here's {} from {} speaker(s).",
+            Mention::Channel(recording.channel),
+            format_duration(recording.duration_secs()),
+            voices.len(),
+        );
+        if let Some(name) = self.save_as {
+            let saved = match recording.saved_as(name) {
+                Some(saved) => saved,
+                None => {
+                    let saved = super::save_as_sound(ctx, name, RECORDING_FILENAME, wav.clone()).await?;
+                    recording.mark_saved(name, saved.clone());
+                    saved
+                },
+            };
+            msg.push_str(&format!("\n{saved}"));
+        }
+
+        ctx.cmd.edit_response(ctx.ctx, EditInteractionResponse::new().content(msg).new_attachment(CreateAttachment::bytes(wav, RECORDING_FILENAME))).await.map_err(|e| {
+            RequestError::Internal(format!("Could not post recording: {e:?}").into())
+        })?;
+        recordings.finish(guild_id);
+
+        Ok(())
+    }
+}
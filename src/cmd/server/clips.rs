@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::{clip, db::{self, guilds::{self, GuildSettingsChanges}}, record, settings};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    enabled: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut enabled = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "enabled" {
+                if let ResolvedValue::Boolean(provided_enabled) = option.value {
+                    enabled = Some(provided_enabled);
+                }
+            }
+        }
+
+        Ok(Self {
+            enabled: enabled.ok_or_else(|| RequestError::User("missing `enabled` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+        super::check_server_admin(ctx)?;
+
+        guilds::update_guild_settings(ctx.db_cfg, &GuildSettingsChanges {
+            guild_id: u64::from(guild_id).into(),
+            clips_enabled: Some(self.enabled),
+            ..Default::default()
+        }).await?;
+
+        let clips = clip::get(ctx.ctx).await.expect("clip manager initialized");
+        if !self.enabled {
+            clips.stop(guild_id);
+            let recordings = record::get(ctx.ctx).await.expect("recording manager initialized");
+            let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+            if let Some(handler) = manager.get(guild_id).filter(|_| !recordings.is_recording(guild_id)) {
+                record::set_decoding(&mut *handler.lock().await, false);
+            }
+            return ctx.reply("Clips are off. Nothing said in voice is kept around anymore.".to_owned()).await;
+        }
+
+        // Start right away if already connected, instead of on the next join.
+        let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+        if let Some(handler) = manager.get(guild_id) {
+            clips.listen(guild_id, &mut *handler.lock().await, db::share_config(ctx.db_cfg));
+        }
+
+        // Everyone should know, so this isn't restricted.
+        ctx.reply(format!(
+            "🔴 Clips are on. While I'm in voice here, I keep the last {} seconds of what's said so anyone can `/clip` it. \
+            `/record optout` leaves you out.",
+            settings::get().clips.buffer_secs,
+        )).await
+    }
+}
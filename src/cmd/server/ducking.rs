@@ -0,0 +1,70 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::{db::guilds::{self, GuildSettingsChanges}, duck};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    enabled: bool,
+    volume: Option<i16>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut enabled = None;
+        let mut volume = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "enabled" {
+                if let ResolvedValue::Boolean(provided_enabled) = option.value {
+                    enabled = Some(provided_enabled);
+                }
+            }
+            if option.name == "volume" {
+                if let ResolvedValue::Integer(provided_volume) = option.value {
+                    volume = Some(i16::try_from(provided_volume).ok().filter(|volume| (0..=100).contains(volume)).ok_or_else(|| {
+                        RequestError::User("`volume` must be between 0 and 100".into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(Self {
+            enabled: enabled.ok_or_else(|| RequestError::User("missing `enabled` required parameter".into()))?,
+            volume,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Without `volume`, keeps whatever the guild had before.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+        super::check_server_admin(ctx)?;
+
+        let guild_settings = guilds::update_guild_settings(ctx.db_cfg, &GuildSettingsChanges {
+            guild_id: u64::from(guild_id).into(),
+            ducking_enabled: Some(self.enabled),
+            ducking_volume: self.volume,
+            ..Default::default()
+        }).await?;
+
+        let ducking = duck::get(ctx.ctx).await.expect("duck manager initialized");
+        if !self.enabled {
+            ducking.stop(guild_id);
+            return ctx.reply("Ducking is off, the music stays put while people talk.".to_owned()).await;
+        }
+
+        // Start right away if already connected, instead of on the next join.
+        let manager = songbird::get(ctx.ctx).await.expect("songbird initialized").clone();
+        if let Some(handler) = manager.get(guild_id) {
+            ducking.listen(guild_id, &mut *handler.lock().await, duck::volume_from_percent(guild_settings.ducking_volume));
+        }
+
+        ctx.reply(format!("Ducking is on, the music drops to {}% while anyone is talking.", guild_settings.ducking_volume)).await
+    }
+}
@@ -0,0 +1,21 @@
+pub mod clips;
+pub mod ducking;
+pub mod now_playing;
+
+use azel::discord::ExecutionContext;
+
+use crate::settings;
+
+use super::RequestError;
+
+/// Server settings are for server admins, and bot owners.
+pub fn check_server_admin(ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+    let is_admin = ctx.cmd.member.as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.administrator());
+    if is_admin || settings::get().owners.contains(&u64::from(ctx.cmd.user.id)) {
+        Ok(())
+    } else {
+        Err(RequestError::User("only server admins can change this".into()))
+    }
+}
@@ -0,0 +1,86 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use serenity::all::{ChannelId, CommandInteraction, Mention, ResolvedValue};
+
+use crate::{db::guilds::{self, GuildSettingsChanges, NowPlayingMode}, now_playing};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    mode: NowPlayingMode,
+    channel: Option<ChannelId>,
+    requested_channel: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut mode = None;
+        let mut channel = None;
+        let mut requested_channel = false;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "mode" {
+                if let ResolvedValue::String(provided_mode) = option.value {
+                    mode = Some(provided_mode.parse().map_err(|_| {
+                        RequestError::User(format!("`{provided_mode}` isn't a mode, pick one of `off`, `compact` or `embed`").into())
+                    })?);
+                }
+            }
+            if option.name == "channel" {
+                if let ResolvedValue::Channel(provided_channel) = option.value {
+                    channel = Some(provided_channel.id);
+                }
+            }
+            if option.name == "requested_channel" {
+                if let ResolvedValue::Boolean(provided_requested_channel) = option.value {
+                    requested_channel = provided_requested_channel;
+                }
+            }
+        }
+        if channel.is_some() && requested_channel {
+            return Err(RequestError::User("pick either a `channel` or `requested_channel`, not both".into()));
+        }
+
+        Ok(Self {
+            mode: mode.ok_or_else(|| RequestError::User("missing `mode` required parameter".into()))?,
+            channel,
+            requested_channel,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Without `channel`, tracks keep getting announced wherever they were before.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+        super::check_server_admin(ctx)?;
+
+        guilds::update_guild_settings(ctx.db_cfg, &GuildSettingsChanges {
+            guild_id: u64::from(guild_id).into(),
+            now_playing_mode: Some(self.mode.as_str()),
+            announce_channel: match (self.channel, self.requested_channel) {
+                (Some(channel), _) => Some(Some(u64::from(channel).into())),
+                (None, true) => Some(None),
+                (None, false) => None,
+            },
+            ..Default::default()
+        }).await?;
+
+        let guild_settings = guilds::load_guild_settings(ctx.db_cfg, u64::from(guild_id).into()).await?;
+        let place = match guild_settings.announce_channel.as_ref().and_then(|channel| channel.to_u64()) {
+            Some(channel) => format!("in {}", Mention::Channel(ChannelId::new(channel))),
+            None => "where each track was requested".to_owned(),
+        };
+        match self.mode {
+            NowPlayingMode::Off => {
+                now_playing::get(ctx.ctx).await.expect("now playing manager initialized").clear(&ctx.ctx.http, guild_id).await;
+                ctx.reply("Tracks won't be announced anymore.".to_owned()).await
+            },
+            NowPlayingMode::Compact => ctx.reply(format!("Tracks get announced in a single line, {place}.")).await,
+            NowPlayingMode::Embed => ctx.reply(format!("Tracks get announced with an embed, {place}.")).await,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+use tracing as trc;
+
+use crate::storage;
+
+use super::super::{play::resolve_uploaded_sound, resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Deletes whichever sound `/play` would pick for the name, if the requester is allowed to manage it.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let sound = resolve_uploaded_sound(ctx, self.name).await?;
+        if !super::can_manage(ctx, &sound) {
+            return Err(RequestError::User("only the uploader or an admin can delete that sound".into()));
+        }
+
+        trc::info!("SOUND-DELETE {:?} {}", sound.link_or_name, sound.id);
+        if !storage::remove_sound(ctx.db_cfg, sound.id).await? {
+            ctx.reply_restricted(format!("**{}** was already deleted.", sound.link_or_name)).await?;
+            return Ok(());
+        }
+
+        ctx.reply_restricted(format!("Deleted **{}**.", sound.link_or_name)).await
+    }
+}
@@ -0,0 +1,57 @@
+use std::{marker::PhantomData, path::Path};
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, CreateAttachment, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedValue};
+use tracing as trc;
+
+use super::super::{play::resolve_uploaded_sound, resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Sends the sound back as an attachment. Prefers the original upload over the transcoded copy, if it was kept.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let sound = resolve_uploaded_sound(ctx, self.name).await?;
+
+        let path = sound.original_file_path.as_deref()
+            .filter(|path| Path::new(path).is_file())
+            .unwrap_or(sound.file_path.as_str());
+        let data = tokio::fs::read(path).await.map_err(|e| {
+            trc::error!("SOUND-DOWNLOAD-FAIL {:?} {e:?}", path);
+            RequestError::Internal("The file for this sound has gone missing! Let the bot owner know.".into())
+        })?;
+        let filename = match Path::new(path).extension() {
+            Some(ext) => format!("{}.{}", sound.link_or_name.replace(['/', '\\'], "_"), ext.to_string_lossy()),
+            None => sound.link_or_name.replace(['/', '\\'], "_"),
+        };
+
+        let response = CreateInteractionResponseMessage::new()
+            .content(format!("Here's **{}**.", sound.link_or_name))
+            .add_file(CreateAttachment::bytes(data, filename))
+            .ephemeral(true);
+        ctx.cmd.create_response(ctx.ctx, CreateInteractionResponse::Message(response)).await.map_err(|e| {
+            RequestError::Internal(format!("sound upload to discord failed {e:?}").into())
+        })
+    }
+}
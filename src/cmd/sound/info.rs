@@ -0,0 +1,62 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use serenity::all::{CommandInteraction, Mention, ResolvedValue, UserId};
+
+use crate::{db::tags, limits::format_duration};
+
+use super::super::{play::resolve_uploaded_sound, resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let sound = resolve_uploaded_sound(ctx, self.name).await?;
+
+        let mut format = vec![sound.codec.clone().unwrap_or_else(|| "unknown format".to_owned())];
+        if let Some(rate) = sound.sample_rate {
+            format.push(format!("{rate} Hz"));
+        }
+        if let Some(channels) = sound.channels {
+            format.push(format!("{channels} ch"));
+        }
+        let duration = sound.duration_secs.map(|duration| format_duration(duration as u64)).unwrap_or_else(|| "unknown".to_owned());
+        let uploader = match sound.uploader.to_u64().filter(|id| *id != 0) {
+            Some(uploader) => Mention::User(UserId::new(uploader)).to_string(),
+            None => "unknown".to_owned(),
+        };
+        let tags = tags::load_tags(ctx.db_cfg, sound.id).await?;
+        let tags = if tags.is_empty() { "none".to_owned() } else { tags.join(", ") };
+
+        let msg = format!(
+            "**{}**\nDuration: {duration}\nFormat: {}\nUploaded by: {uploader}\nShared with: {}\nTags: {tags}\nPlayed {} time(s)",
+            sound.link_or_name,
+            format.join(", "),
+            sound.visibility,
+            sound.play_count,
+        );
+        ctx.reply_restricted(msg).await
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fmt::Write, marker::PhantomData};
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use serenity::all::{CommandInteraction, Mention, ResolvedValue, User, UserId};
+
+use crate::{db, limits::{fit_lines, format_duration, shorten, LISTED_NAME_LENGTH, MESSAGE_LENGTH}};
+
+use super::super::{resolve_subcommand, RequestError};
+
+const PAGE_SIZE: usize = 20;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    page: usize,
+    uploader: Option<&'a User>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut page = None;
+        let mut uploader = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "page" {
+                if let ResolvedValue::Integer(provided_page) = option.value {
+                    page = Some(usize::try_from(provided_page).ok().filter(|page| *page > 0).ok_or_else(|| {
+                        RequestError::User("`page` starts at 1".into())
+                    })?);
+                }
+            }
+            if option.name == "uploader" {
+                if let ResolvedValue::User(provided_uploader, _) = option.value {
+                    uploader = Some(provided_uploader);
+                }
+            }
+        }
+
+        Ok(Self {
+            page: page.unwrap_or(1),
+            uploader,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Lists sounds the requester could play here, the same ones `/play` would find. Only the requested page is
+    /// loaded, so sounds from before guild scoping that turn out to be unreachable can leave a page a little short.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let (sounds, total) = db::load_visible_audio_page(
+            ctx.db_cfg,
+            u64::from(ctx.cmd.user.id).into(),
+            ctx.cmd.guild_id.map(|guild_id| u64::from(guild_id).into()),
+            self.uploader.map(|uploader| u64::from(uploader.id).into()),
+            ((self.page - 1) * PAGE_SIZE) as i64,
+            PAGE_SIZE as i64,
+        ).await?;
+
+        if total == 0 {
+            ctx.reply_restricted("No sounds found.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let pages = (total as usize).div_ceil(PAGE_SIZE);
+        if self.page > pages {
+            ctx.reply_restricted(format!("There {} only {pages} page(s) of sounds.", if pages == 1 { "is" } else { "are" })).await?;
+            return Ok(());
+        }
+
+        let sounds = super::retain_reachable(ctx, sounds).await;
+        let lines: Vec<_> = sounds.iter()
+            .map(|sound| {
+                let mut line = format!("- **{}**", shorten(&sound.link_or_name, LISTED_NAME_LENGTH));
+                // Writing to a String can't fail.
+                if let Some(duration) = sound.duration_secs {
+                    let _ = write!(line, " ({})", format_duration(duration as u64));
+                }
+                if let Some(uploader) = sound.uploader.to_u64().filter(|id| *id != 0) {
+                    let _ = write!(line, " by {}", Mention::User(UserId::new(uploader)));
+                }
+                line
+            })
+            .collect();
+
+        let header = format!("Sounds (page {} of {pages}):\n", self.page);
+        let total = lines.len();
+        let body = fit_lines(lines, total, MESSAGE_LENGTH - header.chars().count());
+        ctx.reply_restricted(format!("{header}{body}")).await
+    }
+}
@@ -0,0 +1,106 @@
+pub mod list;
+pub mod info;
+pub mod rename;
+pub mod delete;
+pub mod download;
+pub mod share;
+pub mod shuffle;
+pub mod tag;
+pub mod search;
+
+use std::collections::HashMap;
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use serenity::all::UserId;
+
+use crate::{db::{tags::MAX_TAG_LENGTH, AudioLedgerEntry, Visibility}, settings};
+
+use super::RequestError;
+
+/// Drops sounds from before guild scoping whose uploader isn't in this guild, matching how `/play` resolves them.
+pub async fn retain_reachable(ctx: &ExecutionContext<'_>, sounds: Vec<AudioLedgerEntry>) -> Vec<AudioLedgerEntry> {
+    let requester = u64::from(ctx.cmd.user.id);
+    let mut membership = HashMap::new();
+    let mut reachable = Vec::with_capacity(sounds.len());
+    for sound in sounds {
+        let uploader = sound.uploader.to_u64().unwrap_or_default();
+        if sound.guild_id.is_some() || uploader == requester || sound.visibility() != Visibility::Guild {
+            reachable.push(sound);
+            continue;
+        }
+        let Some(guild_id) = ctx.cmd.guild_id.filter(|_| uploader != 0) else {
+            continue;
+        };
+        let is_member = match membership.get(&uploader) {
+            Some(is_member) => *is_member,
+            None => {
+                let is_member = guild_id.member(ctx.ctx, UserId::new(uploader)).await.is_ok();
+                membership.insert(uploader, is_member);
+                is_member
+            },
+        };
+        if is_member {
+            reachable.push(sound);
+        }
+    }
+    reachable
+}
+
+/// Uploaders can manage their own sounds. Bot owners can manage any, and server admins the ones shared with their
+/// server.
+pub fn can_manage(ctx: &ExecutionContext<'_>, sound: &AudioLedgerEntry) -> bool {
+    let requester = u64::from(ctx.cmd.user.id);
+    if sound.uploader.to_u64() == Some(requester) || settings::get().owners.contains(&requester) {
+        return true;
+    }
+    let is_admin = ctx.cmd.member.as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.administrator());
+    let in_this_guild = ctx.cmd.guild_id.is_some_and(|guild_id| sound.guild_id.as_ref().and_then(|id| id.to_u64()) == Some(u64::from(guild_id)));
+    is_admin && in_this_guild
+}
+
+/// Splits a comma or space separated list into tags, lowercased and without any leading `#`.
+pub fn parse_tags(raw: &str) -> Result<Vec<String>, RequestError> {
+    let mut tags: Vec<String> = vec![];
+    for tag in raw.split(|c: char| c == ',' || c.is_whitespace()).map(|tag| tag.trim_start_matches('#')).filter(|tag| !tag.is_empty()) {
+        // Lowercasing can change the length, so check what actually gets stored.
+        let tag = tag.to_lowercase();
+        if tag.chars().count() > MAX_TAG_LENGTH || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+            return Err(RequestError::User(format!(
+                "`{tag}` can't be a tag, use up to {MAX_TAG_LENGTH} letters, digits, `-` or `_`",
+            ).into()));
+        }
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.is_empty() {
+        return Err(RequestError::User("give at least one tag".into()));
+    }
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tags_splits_and_normalizes() {
+        assert_eq!(parse_tags(" #Funny, loud  Funny,#meme").unwrap(), vec!["funny", "loud", "meme"]);
+    }
+
+    #[test]
+    fn parse_tags_refuses_bad_tags() {
+        assert!(parse_tags("").is_err());
+        assert!(parse_tags(" , #").is_err());
+        assert!(parse_tags("no!").is_err());
+        assert!(parse_tags(&"a".repeat(MAX_TAG_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn parse_tags_counts_characters() {
+        assert!(parse_tags(&"é".repeat(MAX_TAG_LENGTH)).is_ok());
+    }
+}
@@ -0,0 +1,57 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db;
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    new_name: &'a str,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+        let mut new_name = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+            if option.name == "new_name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    new_name = Some(provided_name.trim());
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            new_name: new_name.filter(|name| !name.is_empty()).ok_or_else(|| RequestError::User("missing `new_name` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Only renames the requester's own sounds, names are per uploader.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let user_id = u64::from(ctx.cmd.user.id);
+        if db::load_maybe_known_audio_in_ledger(ctx.db_cfg, user_id.into(), self.new_name).await?.is_some() {
+            ctx.reply_restricted(format!("You already have a sound called **{}**.", self.new_name)).await?;
+            return Ok(());
+        }
+
+        if !db::rename_audio(ctx.db_cfg, user_id.into(), self.name, self.new_name).await? {
+            ctx.reply_restricted(format!("You don't have a sound called **{}**.", self.name)).await?;
+            return Ok(());
+        }
+
+        ctx.reply_restricted(format!("Renamed **{}** to **{}**.", self.name, self.new_name)).await
+    }
+}
@@ -0,0 +1,104 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue, User};
+
+use crate::{db::tags::{self, SoundSearch}, limits::{escape_markdown, fit_lines, format_duration, shorten, LISTED_NAME_LENGTH, MESSAGE_LENGTH}};
+
+use super::super::{resolve_subcommand, RequestError};
+
+const LISTED_RESULTS: i64 = 20;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: Option<&'a str>,
+    tags: Vec<String>,
+    uploader: Option<&'a User>,
+    min_duration: Option<i64>,
+    max_duration: Option<i64>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+        let mut tags = vec![];
+        let mut uploader = None;
+        let mut min_duration = None;
+        let mut max_duration = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+            if option.name == "tags" {
+                if let ResolvedValue::String(provided_tags) = option.value {
+                    tags = super::parse_tags(provided_tags)?;
+                }
+            }
+            if option.name == "uploader" {
+                if let ResolvedValue::User(provided_uploader, _) = option.value {
+                    uploader = Some(provided_uploader);
+                }
+            }
+            if option.name == "min_duration" {
+                if let ResolvedValue::Integer(provided_min) = option.value {
+                    min_duration = Some(provided_min);
+                }
+            }
+            if option.name == "max_duration" {
+                if let ResolvedValue::Integer(provided_max) = option.value {
+                    max_duration = Some(provided_max);
+                }
+            }
+        }
+
+        if min_duration.zip(max_duration).is_some_and(|(min, max)| min > max) {
+            return Err(RequestError::User("`min_duration` can't be more than `max_duration`".into()));
+        }
+
+        Ok(Self {
+            name,
+            tags,
+            uploader,
+            min_duration,
+            max_duration,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let search = SoundSearch {
+            name: self.name,
+            tags: &self.tags,
+            uploader: self.uploader.map(|uploader| u64::from(uploader.id).into()),
+            min_duration_secs: self.min_duration.map(|secs| secs as f64),
+            max_duration_secs: self.max_duration.map(|secs| secs as f64),
+        };
+        let sounds = tags::search_audio(
+            ctx.db_cfg,
+            u64::from(ctx.cmd.user.id).into(),
+            ctx.cmd.guild_id.map(|id| u64::from(id).into()),
+            &search,
+            LISTED_RESULTS,
+        ).await?;
+        let sounds = super::retain_reachable(ctx, sounds).await;
+        if sounds.is_empty() {
+            ctx.reply_restricted("No sounds match that.".to_owned()).await?;
+            return Ok(());
+        }
+
+        let total = sounds.len();
+        let lines = sounds.iter().enumerate()
+            .map(|(idx, sound)| {
+                let duration = sound.duration_secs.map(|duration| format_duration(duration as u64)).unwrap_or_else(|| "?".to_owned());
+                format!("{}. **{}** ({duration})", idx + 1, escape_markdown(&shorten(&sound.link_or_name, LISTED_NAME_LENGTH)))
+            })
+            .collect();
+        let msg = fit_lines(lines, total, MESSAGE_LENGTH);
+
+        ctx.reply_restricted(msg).await
+    }
+}
@@ -0,0 +1,70 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::{self, AudioSharingChanges, Visibility};
+
+use super::super::{resolve_subcommand, RequestError};
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    visibility: Visibility,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut name = None;
+        let mut visibility = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+            if option.name == "visibility" {
+                if let ResolvedValue::String(provided_visibility) = option.value {
+                    visibility = Some(provided_visibility.to_ascii_lowercase().parse().map_err(|_e| {
+                        RequestError::User("`visibility` must be one of `private`, `guild` or `public`".into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            visibility: visibility.ok_or_else(|| RequestError::User("missing `visibility` required parameter".into()))?,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Only ever touches the requester's own sound. Sharing with a guild shares with the one this is run in.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = match (self.visibility, ctx.cmd.guild_id) {
+            (Visibility::Guild, None) => {
+                return Err(RequestError::User("run this in the server you want to share the sound with".into()));
+            },
+            (Visibility::Guild, Some(guild_id)) => Some(u64::from(guild_id).into()),
+            _ => None,
+        };
+
+        let changes = AudioSharingChanges {
+            visibility: self.visibility.as_str(),
+            guild_id,
+        };
+        if !db::update_audio_sharing(ctx.db_cfg, u64::from(ctx.cmd.user.id).into(), self.name, &changes).await? {
+            ctx.reply_restricted(format!("You don't have a sound called **{}**.", self.name)).await?;
+            return Ok(());
+        }
+
+        let msg = match self.visibility {
+            Visibility::Private => format!("**{}** is now only playable by you.", self.name),
+            Visibility::Guild => format!("**{}** is now playable by anyone in this server.", self.name),
+            Visibility::Public => format!("**{}** is now playable by anyone.", self.name),
+        };
+        ctx.reply_restricted(msg).await
+    }
+}
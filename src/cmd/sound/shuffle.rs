@@ -0,0 +1,65 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{ChannelId, CommandInteraction, ResolvedValue};
+
+use crate::{cmd::playlist::{queue_entries, Order}, db};
+
+use super::super::{resolve_subcommand, RequestError};
+
+const DEFAULT_COUNT: usize = 10;
+const MAX_COUNT: usize = 50;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    count: usize,
+    target: Option<ChannelId>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut count = None;
+        let mut target = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "count" {
+                if let ResolvedValue::Integer(provided_count) = option.value {
+                    count = Some(usize::try_from(provided_count).ok().filter(|count| (1..=MAX_COUNT).contains(count)).ok_or_else(|| {
+                        RequestError::User(format!("`count` goes from 1 to {MAX_COUNT}").into())
+                    })?);
+                }
+            }
+            if option.name == "target" {
+                if let ResolvedValue::Channel(target_channel) = option.value {
+                    target = Some(target_channel.id);
+                }
+            }
+        }
+
+        Ok(Self {
+            count: count.unwrap_or(DEFAULT_COUNT),
+            target,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Picks from the sounds `/sound list` would show, favoring ones the people listening rated highly.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let guild_id = ctx.cmd.guild_id.ok_or_else(|| RequestError::User("command only available in a server".into()))?;
+
+        let sounds = db::load_visible_audio(
+            ctx.db_cfg,
+            u64::from(ctx.cmd.user.id).into(),
+            Some(u64::from(guild_id).into()),
+            None,
+        ).await?;
+        let sounds = super::retain_reachable(ctx, sounds).await;
+        if sounds.is_empty() {
+            ctx.reply_restricted("No sounds here yet, add some with `/upload`.".to_owned()).await?;
+            return Ok(());
+        }
+
+        queue_entries(ctx, "the sound library", sounds, Order::Smart, Some(self.count), self.target, false).await
+    }
+}
@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::{CommandInteraction, ResolvedValue};
+
+use crate::db::tags;
+
+use super::super::{play::resolve_uploaded_sound, resolve_subcommand, RequestError};
+
+/// Most tags a single sound can have.
+const MAX_TAGS: usize = 20;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    name: &'a str,
+    tags: Vec<String>,
+    add: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction, add: bool) -> Result<Self, RequestError> {
+        let mut name = None;
+        let mut tags = None;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "name" {
+                if let ResolvedValue::String(provided_name) = option.value {
+                    name = Some(provided_name);
+                }
+            }
+            if option.name == "tags" {
+                if let ResolvedValue::String(provided_tags) = option.value {
+                    tags = Some(super::parse_tags(provided_tags)?);
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.ok_or_else(|| RequestError::User("missing `name` required parameter".into()))?,
+            tags: tags.ok_or_else(|| RequestError::User("missing `tags` required parameter".into()))?,
+            add,
+            _phantom: &PhantomData,
+        })
+    }
+
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let sound = resolve_uploaded_sound(ctx, self.name).await?;
+        if !super::can_manage(ctx, &sound) {
+            return Err(RequestError::User("only the uploader or an admin can tag that sound".into()));
+        }
+
+        if self.add {
+            let existing = tags::load_tags(ctx.db_cfg, sound.id).await?;
+            let added = self.tags.iter().filter(|tag| !existing.contains(tag)).count();
+            if existing.len() + added > MAX_TAGS {
+                return Err(RequestError::User(format!("sounds can have at most {MAX_TAGS} tags").into()));
+            }
+            tags::add_tags(ctx.db_cfg, sound.id, &self.tags).await?;
+        } else {
+            tags::remove_tags(ctx.db_cfg, sound.id, &self.tags).await?;
+        }
+
+        let current = tags::load_tags(ctx.db_cfg, sound.id).await?;
+        let current = if current.is_empty() { "none".to_owned() } else { current.join(", ") };
+        ctx.reply_restricted(format!("**{}** is now tagged: {current}", sound.link_or_name)).await
+    }
+}
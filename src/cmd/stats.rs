@@ -0,0 +1,183 @@
+use std::{fmt::Write, marker::PhantomData};
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, Utc};
+use chrono_tz::Tz;
+use serenity::all::{CommandInteraction, Mention, ResolvedValue, User, UserId};
+
+use crate::{db::stats::{self, PlayEventKind, StatsScope}, limits::{fit_lines, shorten, LISTED_NAME_LENGTH, MESSAGE_LENGTH}, settings};
+
+use super::RequestError;
+
+const LISTED_ENTRIES: i64 = 5;
+const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    user: Option<&'a User>,
+    days: Option<i64>,
+    timezone: Option<Tz>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut user = None;
+        let mut days = None;
+        let mut timezone = None;
+
+        for option in cmd.data.options().iter() {
+            if option.name == "user" {
+                if let ResolvedValue::User(provided_user, _) = option.value {
+                    user = Some(provided_user);
+                }
+            }
+            if option.name == "days" {
+                if let ResolvedValue::Integer(provided_days) = option.value {
+                    if provided_days <= 0 {
+                        return Err(RequestError::User("`days` must be at least 1".into()));
+                    }
+                    days = Some(provided_days);
+                }
+            }
+            if option.name == "timezone" {
+                if let ResolvedValue::String(provided_timezone) = option.value {
+                    timezone = Some(provided_timezone.parse().map_err(|_| {
+                        RequestError::User(format!("`{provided_timezone}` isn't a known time zone, try something like `Europe/Berlin`").into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(Self {
+            user,
+            days,
+            timezone,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Outside a server, this only ever shows the requester's own plays.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let timezone = match self.timezone {
+            Some(timezone) => timezone,
+            None => default_timezone()?,
+        };
+
+        let user_id = match (ctx.cmd.guild_id, self.user) {
+            (None, Some(user)) if user.id != ctx.cmd.user.id => {
+                return Err(RequestError::User("outside a server you can only look at your own stats".into()));
+            },
+            (_, Some(user)) => Some(user.id),
+            (None, None) => Some(ctx.cmd.user.id),
+            (Some(_), None) => None,
+        };
+        let scope = StatsScope {
+            guild_id: ctx.cmd.guild_id.map(|id| u64::from(id).into()),
+            user_id: user_id.map(|id| u64::from(id).into()),
+            since: self.days.map(|days| Utc::now() - Duration::days(days)),
+            until: None,
+        };
+
+        let top_tracks = stats::load_top_sources(ctx.db_cfg, &scope, PlayEventKind::Start, LISTED_ENTRIES).await?;
+        if top_tracks.is_empty() {
+            ctx.reply_restricted("No plays recorded yet.".to_owned()).await?;
+            return Ok(());
+        }
+        let seconds = stats::total_seconds_listened(ctx.db_cfg, &scope).await?;
+        let hours = buckets::<24>(stats::count_play_starts_by_hour(ctx.db_cfg, &scope, timezone.name()).await?);
+        let weekdays = buckets::<7>(stats::count_play_starts_by_weekday(ctx.db_cfg, &scope, timezone.name()).await?);
+        let plays: usize = hours.iter().sum();
+
+        let mut msg = String::new();
+        let subject = match user_id {
+            Some(user_id) => format!("{}", Mention::User(user_id)),
+            None => "this server".to_owned(),
+        };
+        let period = match self.days {
+            Some(days) => format!(" over the last {days} day(s)"),
+            None => String::new(),
+        };
+        // Writing to a String can't fail.
+        let _ = writeln!(msg, "Stats for {subject}{period}: {} plays, {:.1} hours listened.", plays, seconds / 3600.0);
+
+        let mut rest = String::new();
+        if user_id.is_none() {
+            let requesters = stats::load_top_requesters(ctx.db_cfg, &scope, LISTED_ENTRIES).await?;
+            let _ = writeln!(rest, "\nTop requesters:");
+            for (idx, (user, count)) in requesters.iter().enumerate() {
+                let Some(user) = user.to_u64() else {
+                    continue;
+                };
+                let _ = writeln!(rest, "{}. {} -- {count} plays", idx + 1, Mention::User(UserId::new(user)));
+            }
+        }
+
+        let busiest_hour = busiest(&hours);
+        let busiest_weekday = WEEKDAYS[busiest(&weekdays)];
+        let _ = write!(
+            rest,
+            "\nBusiest times ({timezone}): {busiest_hour:02}:00-{:02}:00, on {busiest_weekday}s.",
+            (busiest_hour + 1) % 24,
+        );
+
+        // Titles are the only thing here that can get long, so they get whatever room the rest leaves.
+        let _ = writeln!(msg, "\nTop tracks:");
+        let lines = top_tracks.iter().enumerate()
+            .map(|(idx, (source, title, count))| format!("{}. {} -- {count} plays", idx + 1, shorten(title.as_deref().unwrap_or(source.as_str()), LISTED_NAME_LENGTH)))
+            .collect();
+        let room = MESSAGE_LENGTH.saturating_sub(msg.chars().count() + rest.chars().count());
+        msg.push_str(&fit_lines(lines, top_tracks.len(), room));
+        msg.push_str(&rest);
+
+        ctx.reply(msg).await
+    }
+}
+
+pub fn default_timezone() -> Result<Tz, RequestError> {
+    settings::get().stats.timezone.parse().map_err(|_| {
+        RequestError::Internal("configured stats time zone is invalid".into())
+    })
+}
+
+/// Spreads `(bucket, count)` rows out into an array, ignoring buckets out of range.
+pub fn buckets<const N: usize>(counts: Vec<(i32, i64)>) -> [usize; N] {
+    let mut buckets = [0; N];
+    for (bucket, count) in counts {
+        if let Some(slot) = usize::try_from(bucket).ok().and_then(|bucket| buckets.get_mut(bucket)) {
+            *slot = usize::try_from(count).unwrap_or_default();
+        }
+    }
+    buckets
+}
+
+/// Index of the largest bucket, the earliest one on ties.
+pub fn busiest(buckets: &[usize]) -> usize {
+    buckets.iter()
+        .enumerate()
+        .fold((0, 0), |(best_idx, best), (idx, count)| if *count > best { (idx, *count) } else { (best_idx, best) })
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_spread_counts_and_skip_out_of_range() {
+        assert_eq!(buckets::<4>(vec![(0, 2), (3, 5), (4, 9), (-1, 9)]), [2, 0, 0, 5]);
+    }
+
+    #[test]
+    fn busiest_picks_the_earliest_of_ties() {
+        assert_eq!(busiest(&[1, 4, 2, 4]), 1);
+        assert_eq!(busiest(&[0; 24]), 0);
+    }
+
+    #[test]
+    fn busiest_hour_of_bucketed_counts() {
+        let hours = buckets::<24>(vec![(8, 3), (22, 7), (23, 1)]);
+        assert_eq!(busiest(&hours), 22);
+    }
+}
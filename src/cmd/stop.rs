@@ -3,6 +3,8 @@ use std::marker::PhantomData;
 use azel::discord::ExecutionContext;
 use serenity::all::CommandInteraction;
 
+use crate::{now_playing, playback};
+
 use super::RequestError;
 
 #[derive(Debug)]
@@ -27,7 +29,9 @@ impl <'a> Request<'a> {
         };
 
         let handler_lock = handler.lock().await;
+        playback::get(ctx.ctx).await.expect("playback manager initialized").end_session(guild_id);
         handler_lock.queue().stop();
+        now_playing::get(ctx.ctx).await.expect("now playing manager initialized").clear(&ctx.ctx.http, guild_id).await;
 
         ctx.reply_restricted("Stopped playback.".to_owned()).await?;
         Ok(())
@@ -1,9 +1,9 @@
-use std::{fs::File, io::Write, marker::PhantomData};
+use std::marker::PhantomData;
 
 use azel::discord::ExecutionContext;
 use serenity::all::{Attachment, CommandInteraction, ResolvedValue};
 
-use crate::db::{self, NewAudioLedgerEntry};
+use crate::{limits::{format_duration, Limits}, probe::{self, AudioProbe}, storage::{self, StoredSound}};
 
 use super::RequestError;
 
@@ -43,38 +43,53 @@ impl <'a> Request<'a> {
         ctx.cmd.defer(ctx.ctx).await.map_err(|_| {
             RequestError::Internal("Could not connect to Discord!".into())
         })?;
-        let (download_path, mut download_output) = generate_filepath(self.sound.filename.as_str())?;
 
-        let new_data = NewAudioLedgerEntry {
-            link_or_name: self.name,
-            downloaded: true,
-            file_path: download_path,
-            uploader: u64::from(ctx.cmd.user.id).into(),
-        };
+        let limits = Limits::for_upload(ctx.cmd.guild_id);
+        limits.check_filesize(Some(u64::from(self.sound.size)))?;
 
         let Ok(download_data) = self.sound.download().await else {
             return Err(RequestError::Internal("Could not download file.".into()));
         };
 
-        let Ok(_) = download_output.write_all(download_data.as_slice()) else {
-            return Err(RequestError::Internal("Could not download file.".into()));
-        };
-
-        db::track_known_audio_in_ledger(ctx.db_cfg, &new_data).await?;
+        // Probing decodes a bit of the file, keep that off the async workers.
+        let probe = tokio::task::spawn_blocking({
+            let filename = self.sound.filename.clone();
+            let data = download_data.clone();
+            move || probe::probe_audio(filename.as_str(), data)
+        }).await
+            .map_err(|e| RequestError::Internal(format!("audio probe failed {e:?}").into()))?
+            .map_err(|reason| RequestError::User(reason.into()))?;
+        limits.check_duration(probe.duration_secs)?;
+
+        let stored = storage::store_sound(ctx.db_cfg, storage::NewSound {
+            name: self.name,
+            filename: self.sound.filename.as_str(),
+            data: download_data.as_slice(),
+            probe,
+            uploader: u64::from(ctx.cmd.user.id).into(),
+            guild_id: ctx.cmd.guild_id.map(|guild_id| u64::from(guild_id).into()),
+        }).await?;
 
-        ctx.reply(format!("Uploaded! {:?}", new_data)).await?;
+        let reply = match stored {
+            StoredSound::Duplicate(existing) => format!("Uploaded **{}**! (same audio as **{existing}**, so it's stored only once)", self.name),
+            StoredSound::New(probe) => format!("Uploaded **{}**! ({})", self.name, describe_probe(&probe)),
+        };
+        ctx.reply(reply).await?;
 
         Ok(())
     }
 }
 
-fn generate_filepath(filename: &str) -> Result<(String, File), RequestError> {
-    loop {
-        let download_path = format!("uploaded/{}/{}", uuid::Uuid::new_v4(), filename);
-        let Ok(f) = File::create(download_path.as_str()) else {
-            continue;
-        };
-
-        return Ok((download_path, f));
+pub fn describe_probe(probe: &AudioProbe) -> String {
+    let mut parts = vec![probe.codec.clone()];
+    if let Some(rate) = probe.sample_rate {
+        parts.push(format!("{rate} Hz"));
+    }
+    if let Some(channels) = probe.channels {
+        parts.push(format!("{channels} ch"));
+    }
+    if let Some(duration) = probe.duration_secs {
+        parts.push(format_duration(duration as u64));
     }
+    parts.join(", ")
 }
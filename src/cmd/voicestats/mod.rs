@@ -0,0 +1,13 @@
+pub mod show;
+pub mod opt_in;
+pub mod purge;
+
+/// Like "1h 05m", or "4m 20s" when under an hour.
+pub fn describe_duration(seconds: f64) -> String {
+    let seconds = seconds.max(0.0).round() as u64;
+    if seconds >= 3600 {
+        format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,41 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::db::voice_stats;
+
+use super::super::RequestError;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    opt_in: bool,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction, opt_in: bool) -> Result<Self, RequestError> {
+        Ok(Self {
+            opt_in,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Applies everywhere, including calls already going on.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let user_id = u64::from(ctx.cmd.user.id).into();
+        if self.opt_in {
+            if voice_stats::opt_in_to_voice_stats(ctx.db_cfg, user_id).await? {
+                ctx.reply_restricted("I'll keep track of how long you speak in voice, just the time and never what's said. \
+                    Anyone can see it with `/voicestats show`.".to_owned()).await
+            } else {
+                ctx.reply_restricted("You were already opted in.".to_owned()).await
+            }
+        } else if voice_stats::opt_out_of_voice_stats(ctx.db_cfg, user_id).await? {
+            ctx.reply_restricted("I've stopped counting, and nobody can see your speaking time anymore. \
+                `/voicestats purge` deletes what was counted so far.".to_owned()).await
+        } else {
+            ctx.reply_restricted("You weren't opted in.".to_owned()).await
+        }
+    }
+}
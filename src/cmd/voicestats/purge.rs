@@ -0,0 +1,34 @@
+use std::marker::PhantomData;
+
+use azel::discord::ExecutionContext;
+use serenity::all::CommandInteraction;
+
+use crate::db::voice_stats;
+
+use super::super::RequestError;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(_cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        Ok(Self {
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Deletes the requester's speaking time in every server. Staying opted in keeps counting from here on.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let user_id = u64::from(ctx.cmd.user.id);
+        let removed = voice_stats::purge_speaking_time(ctx.db_cfg, user_id.into()).await?;
+        let still_counting = !voice_stats::load_opted_in(ctx.db_cfg, &[user_id.into()]).await?.is_empty();
+
+        let mut msg = format!("Deleted your speaking time from {removed} day(s).");
+        if still_counting {
+            msg.push_str(" You're still opted in, so new time keeps counting, `/voicestats optout` stops that.");
+        }
+        ctx.reply_restricted(msg).await
+    }
+}
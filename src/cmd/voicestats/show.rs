@@ -0,0 +1,106 @@
+use std::{fmt::Write, marker::PhantomData};
+
+use azel::discord::ExecutionContext;
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, Utc};
+use serenity::all::{CommandInteraction, Mention, ResolvedValue, User, UserId};
+
+use crate::db::voice_stats::{self, SpeakingTimeScope};
+
+use super::super::{resolve_subcommand, stats::default_timezone, RequestError};
+
+const DEFAULT_DAYS: i64 = 30;
+const LISTED_DAYS: usize = 7;
+const LISTED_SPEAKERS: i64 = 5;
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    user: Option<&'a User>,
+    days: i64,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut user = None;
+        let mut days = DEFAULT_DAYS;
+
+        for option in resolve_subcommand(cmd).1.iter() {
+            if option.name == "user" {
+                if let ResolvedValue::User(provided_user, _) = option.value {
+                    user = Some(provided_user);
+                }
+            }
+            if option.name == "days" {
+                if let ResolvedValue::Integer(provided_days) = option.value {
+                    if provided_days <= 0 {
+                        return Err(RequestError::User("`days` must be at least 1".into()));
+                    }
+                    days = provided_days;
+                }
+            }
+        }
+
+        Ok(Self {
+            user,
+            days,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// Only shows users who opted in. Outside a server, adds up every server.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let user_id = self.user.map_or(ctx.cmd.user.id, |user| user.id);
+        if voice_stats::load_opted_in(ctx.db_cfg, &[u64::from(user_id).into()]).await?.is_empty() {
+            return Err(RequestError::User(if user_id == ctx.cmd.user.id {
+                "you haven't opted in, `/voicestats optin` starts counting".into()
+            } else {
+                format!("{} hasn't opted in to voice stats", Mention::User(user_id)).into()
+            }));
+        }
+
+        let today = Utc::now().with_timezone(&default_timezone()?).date_naive();
+        let guild_id = ctx.cmd.guild_id.map(|id| u64::from(id).into());
+        let since = today - Duration::days(self.days - 1);
+        let daily = voice_stats::load_daily_speaking_time(ctx.db_cfg, &SpeakingTimeScope {
+            guild_id: guild_id.clone(),
+            user_id: Some(u64::from(user_id).into()),
+            since: Some(since),
+        }).await?;
+
+        let mut msg = String::new();
+        let total: f64 = daily.iter().map(|(_, seconds)| seconds).sum();
+        // Writing to a String can't fail.
+        let _ = writeln!(
+            msg,
+            "{} spoke for {} over the last {} day(s), on {} of them.",
+            Mention::User(user_id), super::describe_duration(total), self.days, daily.len(),
+        );
+        if let Some((day, seconds)) = daily.iter().max_by(|a, b| a.1.total_cmp(&b.1)) {
+            let _ = writeln!(msg, "Chattiest day: {} with {}.", day.format("%a %Y-%m-%d"), super::describe_duration(*seconds));
+        }
+        if !daily.is_empty() {
+            let _ = writeln!(msg, "\nMost recent days:");
+            for (day, seconds) in daily.iter().rev().take(LISTED_DAYS) {
+                let _ = writeln!(msg, "{} -- {}", day.format("%a %Y-%m-%d"), super::describe_duration(*seconds));
+            }
+        }
+
+        if guild_id.is_some() {
+            let speakers = voice_stats::load_top_speakers(ctx.db_cfg, &SpeakingTimeScope {
+                guild_id,
+                user_id: None,
+                since: Some(since),
+            }, LISTED_SPEAKERS).await?;
+            let _ = writeln!(msg, "\nTop talkers here:");
+            for (idx, (user, seconds)) in speakers.iter().enumerate() {
+                let Some(user) = user.to_u64() else {
+                    continue;
+                };
+                let _ = writeln!(msg, "{}. {} -- {}", idx + 1, Mention::User(UserId::new(user)), super::describe_duration(*seconds));
+            }
+        }
+
+        ctx.reply(msg).await
+    }
+}
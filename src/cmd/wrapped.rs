@@ -0,0 +1,272 @@
+use std::{marker::PhantomData, time::Duration};
+
+use azel::discord::ExecutionContext;
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serenity::all::{ButtonStyle, CommandInteraction, ComponentInteractionCollector, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse, ResolvedValue, User};
+use tracing as trc;
+
+use crate::{db::stats::{self, PlayEventKind, StatsScope}, limits::{fit_lines, shorten, EMBED_FIELD_LENGTH, LISTED_NAME_LENGTH}};
+
+use super::{stats::{buckets, busiest, default_timezone}, RequestError};
+
+const TOP_TRACKS: i64 = 5;
+/// How long the page buttons keep working after the last press.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const PREVIOUS_PAGE: &str = "wrapped-previous";
+const NEXT_PAGE: &str = "wrapped-next";
+
+#[derive(Debug)]
+pub struct Request<'a> {
+    year: Option<i32>,
+    user: Option<&'a User>,
+    timezone: Option<Tz>,
+    _phantom: &'a PhantomData<()>,
+}
+
+impl <'a> Request<'a> {
+    pub fn parse(cmd: &'a CommandInteraction) -> Result<Self, RequestError> {
+        let mut year = None;
+        let mut user = None;
+        let mut timezone = None;
+
+        for option in cmd.data.options().iter() {
+            if option.name == "year" {
+                if let ResolvedValue::Integer(provided_year) = option.value {
+                    year = Some(i32::try_from(provided_year).ok().filter(|year| (2000..=9999).contains(year)).ok_or_else(|| {
+                        RequestError::User(format!("{provided_year} isn't a year we have plays for").into())
+                    })?);
+                }
+            }
+            if option.name == "user" {
+                if let ResolvedValue::User(provided_user, _) = option.value {
+                    user = Some(provided_user);
+                }
+            }
+            if option.name == "timezone" {
+                if let ResolvedValue::String(provided_timezone) = option.value {
+                    timezone = Some(provided_timezone.parse().map_err(|_| {
+                        RequestError::User(format!("`{provided_timezone}` isn't a known time zone, try something like `Europe/Berlin`").into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(Self {
+            year,
+            user,
+            timezone,
+            _phantom: &PhantomData,
+        })
+    }
+
+    /// One page for the user, and in a server, one more for the whole server.
+    pub async fn execute(self, ctx: &ExecutionContext<'_>) -> Result<(), RequestError> {
+        let timezone = match self.timezone {
+            Some(timezone) => timezone,
+            None => default_timezone()?,
+        };
+        let year = self.year.unwrap_or_else(|| Utc::now().with_timezone(&timezone).year());
+        let (Some(since), Some(until)) = (start_of_year(&timezone, year), start_of_year(&timezone, year + 1)) else {
+            return Err(RequestError::User(format!("{year} doesn't start cleanly in {timezone}").into()));
+        };
+
+        let user = self.user.unwrap_or(&ctx.cmd.user);
+        if ctx.cmd.guild_id.is_none() && user.id != ctx.cmd.user.id {
+            return Err(RequestError::User("outside a server you can only look at your own wrapped".into()));
+        }
+        let guild_id = ctx.cmd.guild_id.map(|id| u64::from(id).into());
+        let mut scopes = vec![(
+            format!("{}'s {year} wrapped", user.display_name()),
+            StatsScope {
+                guild_id: guild_id.clone(),
+                user_id: Some(u64::from(user.id).into()),
+                since: Some(since),
+                until: Some(until),
+            },
+        )];
+        if guild_id.is_some() {
+            scopes.push((
+                format!("This server's {year} wrapped"),
+                StatsScope {
+                    guild_id,
+                    user_id: None,
+                    since: Some(since),
+                    until: Some(until),
+                },
+            ));
+        }
+
+        let mut recaps = Vec::with_capacity(scopes.len());
+        for (heading, scope) in scopes {
+            recaps.push(Recap::load(ctx, heading, &scope, &timezone).await?);
+        }
+        let pages: Vec<_> = recaps.iter()
+            .enumerate()
+            .map(|(idx, recap)| recap.embed(year, &timezone).footer(CreateEmbedFooter::new(format!("Page {}/{}", idx + 1, recaps.len()))))
+            .collect();
+
+        let mut page = 0;
+        let response = CreateInteractionResponseMessage::new()
+            .embed(pages[page].clone())
+            .components(page_buttons(page, pages.len()));
+        ctx.cmd.create_response(ctx.ctx, CreateInteractionResponse::Message(response)).await.map_err(|e| {
+            RequestError::Internal(format!("Could not send recap: {e:?}").into())
+        })?;
+        if pages.len() == 1 {
+            return Ok(());
+        }
+
+        let message = ctx.cmd.get_response(ctx.ctx).await.map_err(|e| {
+            RequestError::Internal(format!("Could not find recap message: {e:?}").into())
+        })?;
+        while let Some(press) = ComponentInteractionCollector::new(ctx.ctx)
+            .message_id(message.id)
+            .timeout(PAGE_TIMEOUT)
+            .await
+        {
+            page = match press.data.custom_id.as_str() {
+                PREVIOUS_PAGE => page.saturating_sub(1),
+                NEXT_PAGE => (page + 1).min(pages.len() - 1),
+                _ => page,
+            };
+            let update = CreateInteractionResponseMessage::new()
+                .embed(pages[page].clone())
+                .components(page_buttons(page, pages.len()));
+            if let Err(e) = press.create_response(ctx.ctx, CreateInteractionResponse::UpdateMessage(update)).await {
+                trc::warn!("WRAPPED-PAGE-FAIL {e:?}");
+            }
+        }
+
+        // Buttons that do nothing are worse than none.
+        ctx.cmd.edit_response(ctx.ctx, EditInteractionResponse::new().components(vec![])).await.map_err(|e| {
+            RequestError::Internal(format!("Could not update recap: {e:?}").into())
+        })?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Recap {
+    heading: String,
+    plays: usize,
+    top_tracks: Vec<(String, Option<String>, i64)>,
+    seconds_listened: f64,
+    longest_streak: usize,
+    most_skipped: Option<(String, Option<String>, i64)>,
+    busiest_hour: usize,
+}
+
+impl Recap {
+    async fn load(ctx: &ExecutionContext<'_>, heading: String, scope: &StatsScope, timezone: &Tz) -> Result<Self, RequestError> {
+        let top_tracks = stats::load_top_sources(ctx.db_cfg, scope, PlayEventKind::Start, TOP_TRACKS).await?;
+        let most_skipped = stats::load_top_sources(ctx.db_cfg, scope, PlayEventKind::Skip, 1).await?.pop();
+        let seconds_listened = stats::total_seconds_listened(ctx.db_cfg, scope).await?;
+        let hours = buckets::<24>(stats::count_play_starts_by_hour(ctx.db_cfg, scope, timezone.name()).await?);
+        let days = stats::load_play_days(ctx.db_cfg, scope, timezone.name()).await?;
+
+        Ok(Self {
+            heading,
+            plays: hours.iter().sum(),
+            top_tracks,
+            seconds_listened,
+            longest_streak: longest_streak(days),
+            most_skipped,
+            busiest_hour: busiest(&hours),
+        })
+    }
+
+    fn embed(&self, year: i32, timezone: &Tz) -> CreateEmbed {
+        let embed = CreateEmbed::new().title(self.heading.as_str());
+        if self.plays == 0 {
+            return embed.description(format!("No plays recorded in {year}."));
+        }
+
+        let top_tracks = self.top_tracks.iter().enumerate()
+            .map(|(idx, track)| format!("{}. {} -- {} plays", idx + 1, describe(track), track.2))
+            .collect();
+        let top_tracks = fit_lines(top_tracks, self.top_tracks.len(), EMBED_FIELD_LENGTH);
+        let most_skipped = match self.most_skipped.as_ref() {
+            Some(track) => shorten(format!("{} -- {} skips", describe(track), track.2).as_str(), EMBED_FIELD_LENGTH),
+            None => "Nothing, not once.".to_owned(),
+        };
+
+        embed
+            .description(format!("{} plays in {year}.", self.plays))
+            .field("Top tracks", top_tracks, false)
+            .field("Minutes listened", format!("{:.0}", self.seconds_listened / 60.0), true)
+            .field("Longest streak", format!("{} day(s)", self.longest_streak), true)
+            .field(
+                format!("Most active hour ({timezone})"),
+                format!("{:02}:00-{:02}:00", self.busiest_hour, (self.busiest_hour + 1) % 24),
+                true,
+            )
+            .field("Most skipped", most_skipped, false)
+    }
+}
+
+fn describe((source, title, _): &(String, Option<String>, i64)) -> String {
+    match title.as_deref() {
+        Some(title) if source.starts_with("http") => format!("[{}]({source})", shorten(title, LISTED_NAME_LENGTH)),
+        Some(title) => shorten(title, LISTED_NAME_LENGTH),
+        None => shorten(source, LISTED_NAME_LENGTH),
+    }
+}
+
+fn start_of_year(timezone: &Tz, year: i32) -> Option<chrono::DateTime<Utc>> {
+    timezone.with_ymd_and_hms(year, 1, 1, 0, 0, 0).earliest().map(|start| start.with_timezone(&Utc))
+}
+
+/// Most consecutive days with at least one play. `days` must be in order.
+fn longest_streak(days: Vec<NaiveDate>) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut last: Option<NaiveDate> = None;
+    for day in days {
+        match last {
+            Some(last) if last >= day => continue,
+            Some(last) if last.succ_opt() == Some(day) => current += 1,
+            _ => current = 1,
+        }
+        last = Some(day);
+        longest = longest.max(current);
+    }
+    longest
+}
+
+fn page_buttons(page: usize, pages: usize) -> Vec<CreateActionRow> {
+    if pages <= 1 {
+        return vec![];
+    }
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(PREVIOUS_PAGE).label("Previous").style(ButtonStyle::Secondary).disabled(page == 0),
+        CreateButton::new(NEXT_PAGE).label("Next").style(ButtonStyle::Secondary).disabled(page + 1 >= pages),
+    ])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(days: &[(u32, u32)]) -> Vec<NaiveDate> {
+        days.iter().map(|&(month, day)| NaiveDate::from_ymd_opt(2024, month, day).unwrap()).collect()
+    }
+
+    #[test]
+    fn longest_streak_counts_consecutive_days() {
+        assert_eq!(longest_streak(vec![]), 0);
+        assert_eq!(longest_streak(days(&[(3, 1)])), 1);
+        assert_eq!(longest_streak(days(&[(3, 1), (3, 2), (3, 4), (3, 5), (3, 6)])), 3);
+    }
+
+    #[test]
+    fn longest_streak_spans_months_and_leap_days() {
+        assert_eq!(longest_streak(days(&[(2, 28), (2, 29), (3, 1)])), 3);
+    }
+
+    #[test]
+    fn longest_streak_ignores_repeated_days() {
+        assert_eq!(longest_streak(days(&[(3, 1), (3, 1), (3, 2)])), 2);
+    }
+}
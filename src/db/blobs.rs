@@ -0,0 +1,158 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, OptionalExtension, Selectable, dsl::{exists, not}, prelude::{Identifiable, Insertable, QueryDsl, Queryable}};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+
+use crate::schema::{audio_blobs, audio_ledger};
+
+use super::{connect, AudioLedgerEntry, NewAudioLedgerEntry};
+
+/// Uploaded audio, stored once per distinct content. `ref_count` is the number of ledger entries pointing at it.
+#[derive(Debug)]
+#[derive(Queryable, Identifiable, Selectable)]
+#[diesel(table_name = audio_blobs, primary_key(hash))]
+pub struct AudioBlob {
+    pub hash: String,
+    pub directory: String,
+    pub ref_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = audio_blobs)]
+pub struct NewAudioBlob<'a> {
+    pub hash: &'a str,
+    pub directory: &'a str,
+}
+
+/// Any ledger entry using the blob, to copy its file details from.
+pub async fn load_blob_reference(cfg: &DatabaseConfiguration, hash: &str) -> Result<Option<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .filter(audio_ledger::blob_hash.eq(hash))
+            .order(audio_ledger::id.asc())
+            .first(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Records `entry` as another reference to the existing blob `hash`. Returns false, adding nothing, if the blob was
+/// removed in the meantime.
+pub async fn add_audio_reference(cfg: &DatabaseConfiguration, hash: &str, entry: &NewAudioLedgerEntry<'_>) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+            // Locks the blob row, so a concurrent release waits for this to be done.
+            let updated = diesel::update(audio_blobs::table.find(hash))
+                .set(audio_blobs::ref_count.eq(audio_blobs::ref_count + 1))
+                .execute(conn)
+                .await?;
+            if updated == 0 {
+                return Ok(false);
+            }
+            diesel::insert_into(audio_ledger::table)
+                .values(entry)
+                .execute(conn)
+                .await?;
+            Ok(true)
+        }.scope_boxed()).await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Whether any ledger entry uses the blob `hash`.
+pub async fn is_blob_referenced(cfg: &DatabaseConfiguration, hash: &str) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        diesel::select(exists(audio_ledger::table.filter(audio_ledger::blob_hash.eq(hash))))
+            .get_result(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Records `entry` as a new reference to `blob`, creating the blob if this is its first one.
+pub async fn track_audio_reference(cfg: &DatabaseConfiguration, blob: &NewAudioBlob<'_>, entry: &NewAudioLedgerEntry<'_>) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+            diesel::insert_into(audio_blobs::table)
+                .values((blob, audio_blobs::ref_count.eq(1)))
+                .on_conflict(audio_blobs::hash)
+                .do_update()
+                .set(audio_blobs::ref_count.eq(audio_blobs::ref_count + 1))
+                .execute(conn)
+                .await?;
+            diesel::insert_into(audio_ledger::table)
+                .values(entry)
+                .execute(conn)
+                .await?;
+            Ok(())
+        }.scope_boxed()).await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Removes a ledger entry, along with its blob if nothing else references it anymore.
+///
+/// Returns the removed entry and, if it was the last reference, the removed blob. Deleting their files is up to
+/// the caller.
+pub async fn release_audio_reference(cfg: &DatabaseConfiguration, id: i64) -> Result<Option<(AudioLedgerEntry, Option<AudioBlob>)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+            let Some(entry) = ({
+                diesel::delete(audio_ledger::table.find(id))
+                    .get_result::<AudioLedgerEntry>(conn)
+                    .await
+                    .optional()?
+            }) else {
+                return Ok(None);
+            };
+            let Some(hash) = entry.blob_hash.as_deref() else {
+                return Ok(Some((entry, None)));
+            };
+
+            let blob: AudioBlob = diesel::update(audio_blobs::table.find(hash))
+                .set(audio_blobs::ref_count.eq(audio_blobs::ref_count - 1))
+                .get_result(conn)
+                .await?;
+            if blob.ref_count > 0 {
+                return Ok(Some((entry, None)));
+            }
+
+            // Only if nothing took a new reference since, see `add_audio_reference`.
+            let removed = diesel::delete(audio_blobs::table.find(hash))
+                .filter(audio_blobs::ref_count.le(0))
+                .filter(not(exists(audio_ledger::table.filter(audio_ledger::blob_hash.eq(hash)))))
+                .execute(conn)
+                .await?;
+            Ok(Some((entry, (removed > 0).then_some(blob))))
+        }.scope_boxed()).await
+    }) else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(val)
+}
@@ -0,0 +1,108 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Selectable, dsl::sum, prelude::{Identifiable, Insertable, QueryDsl, Queryable}};
+use diesel_async::RunQueryDsl;
+
+use crate::schema::download_cache;
+
+use super::connect;
+
+#[derive(Debug)]
+#[derive(Queryable, Identifiable, Selectable)]
+#[diesel(table_name = download_cache, primary_key(video_id))]
+pub struct CachedDownload {
+    pub video_id: String,
+    pub file_path: String,
+    pub size_bytes: i64,
+    pub last_played: DateTime<Utc>,
+    pub pinned: bool,
+}
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = download_cache)]
+pub struct NewCachedDownload<'a> {
+    pub video_id: &'a str,
+    pub file_path: &'a str,
+    pub size_bytes: i64,
+    pub last_played: DateTime<Utc>,
+}
+
+pub async fn record_cached_download(cfg: &DatabaseConfiguration, data: &NewCachedDownload<'_>) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::insert_into(download_cache::table)
+            .values(data)
+            .on_conflict(download_cache::video_id)
+            .do_update()
+            .set((
+                download_cache::file_path.eq(data.file_path),
+                download_cache::size_bytes.eq(data.size_bytes),
+                download_cache::last_played.eq(data.last_played),
+            ))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+pub async fn set_cached_download_pinned(cfg: &DatabaseConfiguration, video_id: &str, pinned: bool) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(updated) = ({
+        diesel::update(download_cache::table.find(video_id))
+            .set(download_cache::pinned.eq(pinned))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database update failed".into()));
+    };
+
+    Ok(updated > 0)
+}
+
+/// All cache entries, least recently played first.
+pub async fn load_cached_downloads_by_age(cfg: &DatabaseConfiguration) -> Result<Vec<CachedDownload>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        download_cache::table
+            .order(download_cache::last_played.asc())
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+pub async fn total_cached_download_size(cfg: &DatabaseConfiguration) -> Result<i64, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        download_cache::table
+            .select(sum(download_cache::size_bytes))
+            .get_result::<Option<BigDecimal>>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val.and_then(|total| total.to_i64()).unwrap_or(0))
+}
+
+pub async fn remove_cached_download(cfg: &DatabaseConfiguration, video_id: &str) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = diesel::delete(download_cache::table.find(video_id)).execute(&mut conn).await else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(())
+}
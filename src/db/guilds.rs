@@ -0,0 +1,115 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use diesel::{OptionalExtension, Selectable, prelude::{AsChangeset, Identifiable, Insertable, QueryDsl, Queryable}};
+use diesel_async::RunQueryDsl;
+use strum::{EnumString, IntoStaticStr};
+
+use crate::schema::guild_settings;
+
+use super::connect;
+
+/// Matches the `guild_settings.ducking_volume` column default.
+pub const DEFAULT_DUCKING_VOLUME: i16 = 20;
+
+/// How tracks starting get announced in chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumString, IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum NowPlayingMode {
+    Off,
+    /// A single line.
+    Compact,
+    /// An embed with the thumbnail and details.
+    Embed,
+}
+
+impl NowPlayingMode {
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Per guild switches, set with `/server`. Guilds that never changed anything don't have a row.
+#[derive(Debug, Clone)]
+#[derive(Queryable, Identifiable, Selectable)]
+#[diesel(table_name = guild_settings, primary_key(guild_id))]
+pub struct GuildSettings {
+    pub guild_id: BigDecimal,
+    /// Keep the last stretch of voice around for `/clip`.
+    pub clips_enabled: bool,
+    /// Turn the music down while anyone is talking.
+    pub ducking_enabled: bool,
+    /// How loud the music gets while ducked, in percent of full volume.
+    pub ducking_volume: i16,
+    /// One of [`NowPlayingMode`], see [`GuildSettings::now_playing_mode`].
+    pub now_playing_mode: String,
+    /// Where to announce tracks, instead of where each was requested.
+    pub announce_channel: Option<BigDecimal>,
+}
+
+impl GuildSettings {
+    /// What a guild without a row gets, matching the column defaults.
+    fn defaults(guild_id: BigDecimal) -> Self {
+        Self {
+            guild_id,
+            clips_enabled: false,
+            ducking_enabled: false,
+            ducking_volume: DEFAULT_DUCKING_VOLUME,
+            now_playing_mode: NowPlayingMode::Off.as_str().to_owned(),
+            announce_channel: None,
+        }
+    }
+
+    /// Unknown modes count as off.
+    pub fn now_playing_mode(&self) -> NowPlayingMode {
+        self.now_playing_mode.parse().unwrap_or(NowPlayingMode::Off)
+    }
+}
+
+/// Unset fields are left alone.
+#[derive(Debug, Default)]
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = guild_settings, primary_key(guild_id))]
+pub struct GuildSettingsChanges {
+    pub guild_id: BigDecimal,
+    pub clips_enabled: Option<bool>,
+    pub ducking_enabled: Option<bool>,
+    pub ducking_volume: Option<i16>,
+    pub now_playing_mode: Option<&'static str>,
+    /// `Some(None)` goes back to announcing where each track was requested.
+    pub announce_channel: Option<Option<BigDecimal>>,
+}
+
+pub async fn load_guild_settings(cfg: &DatabaseConfiguration, guild_id: BigDecimal) -> Result<GuildSettings, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        guild_settings::table
+            .find(&guild_id)
+            .first(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val.unwrap_or_else(|| GuildSettings::defaults(guild_id)))
+}
+
+pub async fn update_guild_settings(cfg: &DatabaseConfiguration, changes: &GuildSettingsChanges) -> Result<GuildSettings, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        diesel::insert_into(guild_settings::table)
+            .values(changes)
+            .on_conflict(guild_settings::guild_id)
+            .do_update()
+            .set(changes)
+            .get_result(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database update failed".into()));
+    };
+
+    Ok(val)
+}
@@ -0,0 +1,83 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use chrono::{DateTime, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, Selectable, upsert::excluded, prelude::{Identifiable, Insertable, QueryDsl, Queryable}};
+use diesel_async::RunQueryDsl;
+
+use crate::schema::media_metadata;
+
+use super::connect;
+
+/// Extractor name yt-dlp reports for regular youtube videos.
+pub const YOUTUBE_EXTRACTOR: &str = "youtube";
+
+#[derive(Debug, Clone)]
+#[derive(Queryable, Identifiable, Selectable)]
+#[diesel(table_name = media_metadata)]
+pub struct MediaMetadata {
+    pub id: i64,
+    pub extractor: String,
+    pub video_id: String,
+    pub url: String,
+    pub title: String,
+    pub uploader: Option<String>,
+    pub duration_secs: Option<f64>,
+    pub thumbnail_url: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = media_metadata)]
+pub struct NewMediaMetadata<'a> {
+    pub extractor: &'a str,
+    pub video_id: &'a str,
+    pub url: &'a str,
+    pub title: &'a str,
+    pub uploader: Option<&'a str>,
+    pub duration_secs: Option<f64>,
+    pub thumbnail_url: Option<&'a str>,
+}
+
+pub async fn record_media_metadata(cfg: &DatabaseConfiguration, data: &NewMediaMetadata<'_>) -> Result<MediaMetadata, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        diesel::insert_into(media_metadata::table)
+            .values(data)
+            .on_conflict((media_metadata::extractor, media_metadata::video_id))
+            .do_update()
+            .set((
+                media_metadata::url.eq(excluded(media_metadata::url)),
+                media_metadata::title.eq(excluded(media_metadata::title)),
+                media_metadata::uploader.eq(excluded(media_metadata::uploader)),
+                media_metadata::duration_secs.eq(excluded(media_metadata::duration_secs)),
+                media_metadata::thumbnail_url.eq(excluded(media_metadata::thumbnail_url)),
+                media_metadata::fetched_at.eq(Utc::now()),
+            ))
+            .get_result(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(val)
+}
+
+pub async fn load_media_metadata(cfg: &DatabaseConfiguration, extractor: &str, video_id: &str) -> Result<Option<MediaMetadata>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        media_metadata::table
+            .filter(
+                media_metadata::extractor.eq(extractor)
+                .and(media_metadata::video_id.eq(video_id))
+            )
+            .get_result(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
@@ -1,7 +1,21 @@
+pub mod blobs;
+pub mod cache;
+pub mod guilds;
+pub mod metadata;
+pub mod playlists;
+pub mod ratings;
+pub mod recording;
+pub mod stats;
+pub mod tags;
+pub mod voice_stats;
+
+use std::sync::Arc;
+
 use azel::{cmd::RequestError, DatabaseConfiguration};
 use bigdecimal::{BigDecimal};
-use diesel::{BoolExpressionMethods, Expression, ExpressionMethods, OptionalExtension, Selectable, prelude::{Identifiable, Insertable, QueryDsl, Queryable}};
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, PgExpressionMethods, Selectable, pg::Pg, prelude::{AsChangeset, Identifiable, Insertable, QueryDsl, Queryable}};
 use diesel_async::{AsyncPgConnection, AsyncConnection, RunQueryDsl};
+use strum::{EnumString, IntoStaticStr};
 
 use crate::schema::audio_ledger;
 
@@ -11,9 +25,47 @@ use crate::schema::audio_ledger;
 pub struct AudioLedgerEntry {
     pub id: i64,
     pub link_or_name: String,
+    /// Set for uploaded sounds, whose audio is at `file_path`. Links (see [`track_link_in_ledger`]) only remember
+    /// what to play, and are left out of sound lookups.
     pub downloaded: bool,
     pub file_path: String,
     pub uploader: BigDecimal,
+    pub codec: Option<String>,
+    pub sample_rate: Option<i32>,
+    pub channels: Option<i16>,
+    pub duration_secs: Option<f64>,
+    pub original_file_path: Option<String>,
+    /// Set for sounds stored through [`blobs`]. Sounds recorded before that own their file outright.
+    pub blob_hash: Option<String>,
+    /// Guild the sound was uploaded in. Missing for sounds uploaded in DMs or recorded before this was tracked.
+    pub guild_id: Option<BigDecimal>,
+    pub visibility: String,
+    pub play_count: i64,
+}
+
+impl AudioLedgerEntry {
+    pub fn visibility(&self) -> Visibility {
+        self.visibility.parse().unwrap_or(Visibility::Private)
+    }
+}
+
+/// Who besides the uploader can play a sound by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumString, IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum Visibility {
+    /// Only the uploader.
+    Private,
+    /// Anyone in the guild it was uploaded in.
+    Guild,
+    /// Anyone, anywhere.
+    Public,
+}
+
+impl Visibility {
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
 }
 
 #[derive(Debug)]
@@ -24,12 +76,50 @@ pub struct NewAudioLedgerEntry<'a> {
     pub downloaded: bool,
     pub file_path: String,
     pub uploader: BigDecimal,
+    pub codec: Option<&'a str>,
+    pub sample_rate: Option<i32>,
+    pub channels: Option<i16>,
+    pub duration_secs: Option<f64>,
+    pub original_file_path: Option<String>,
+    pub blob_hash: Option<&'a str>,
+    pub guild_id: Option<BigDecimal>,
+    pub visibility: &'a str,
+}
+
+/// Changes the visibility of a sound. `guild_id` is written even when `None`, so a sound
+/// that stops being shared with a guild doesn't stay tied to it.
+#[derive(Debug)]
+#[derive(AsChangeset)]
+#[diesel(table_name = audio_ledger, treat_none_as_null = true)]
+pub struct AudioSharingChanges<'a> {
+    pub visibility: &'a str,
+    pub guild_id: Option<BigDecimal>,
+}
+
+/// New file details for a sound whose audio got replaced, e.g. by transcoding.
+#[derive(Debug)]
+#[derive(AsChangeset)]
+#[diesel(table_name = audio_ledger)]
+pub struct AudioFileChanges<'a> {
+    pub file_path: &'a str,
+    pub original_file_path: Option<&'a str>,
+    pub codec: Option<&'a str>,
+    pub sample_rate: Option<i32>,
+    pub channels: Option<i16>,
+    pub duration_secs: Option<f64>,
+}
+
+pub(crate) async fn connect(cfg: &DatabaseConfiguration) -> Result<AsyncPgConnection, RequestError> {
+    AsyncPgConnection::establish(&cfg.url).await.map_err(|_e| RequestError::User("Database connection failed".into()))
+}
+
+/// A copy of `cfg` for work that outlives the command it came from, since the configuration itself isn't `Clone`.
+pub fn share_config(cfg: &DatabaseConfiguration) -> Arc<DatabaseConfiguration> {
+    Arc::new(DatabaseConfiguration { url: cfg.url.clone() })
 }
 
 pub async fn track_known_audio_in_ledger(cfg: &DatabaseConfiguration, data: &NewAudioLedgerEntry<'_>) -> Result<(), RequestError> {
-    let Ok(mut conn) = AsyncPgConnection::establish(&cfg.url).await else {
-        return Err(RequestError::User("Database connection failed".into()));
-    };
+    let mut conn = connect(cfg).await?;
 
     let Ok(_) = diesel::insert_into(audio_ledger::table).values(data).execute(&mut conn).await else {
         return Err(RequestError::User("Database insert failed".into()));
@@ -39,15 +129,14 @@ pub async fn track_known_audio_in_ledger(cfg: &DatabaseConfiguration, data: &New
 }
 
 pub async fn load_maybe_known_audio_in_ledger(cfg: &DatabaseConfiguration, user_id: BigDecimal, name: &str) -> Result<Option<AudioLedgerEntry>, RequestError> {
-    let Ok(mut conn) = AsyncPgConnection::establish(&cfg.url).await else {
-        return Err(RequestError::User("Database connection failed".into()));
-    };
+    let mut conn = connect(cfg).await?;
 
     let Ok(val) = ({
         audio_ledger::table
             .filter(
                 audio_ledger::uploader.eq(user_id)
                 .and(audio_ledger::link_or_name.eq(name))
+                .and(audio_ledger::downloaded.eq(true))
             )
             .get_result(&mut conn)
             .await
@@ -58,3 +147,294 @@ pub async fn load_maybe_known_audio_in_ledger(cfg: &DatabaseConfiguration, user_
 
     Ok(val)
 }
+
+/// Ledger entry remembering `data.link_or_name` as a link for its uploader, created if they don't have one yet.
+pub async fn track_link_in_ledger(cfg: &DatabaseConfiguration, data: &NewAudioLedgerEntry<'_>) -> Result<AudioLedgerEntry, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::insert_into(audio_ledger::table)
+            .values(data)
+            .on_conflict((audio_ledger::uploader, audio_ledger::link_or_name))
+            .do_nothing()
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .filter(
+                audio_ledger::uploader.eq(&data.uploader)
+                .and(audio_ledger::link_or_name.eq(data.link_or_name))
+            )
+            .get_result(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+pub async fn load_audio(cfg: &DatabaseConfiguration, id: i64) -> Result<Option<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .find(id)
+            .first(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Oldest sound called `name` that was shared with `guild_id`.
+pub async fn load_guild_audio_by_name(cfg: &DatabaseConfiguration, guild_id: BigDecimal, name: &str) -> Result<Option<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .filter(
+                audio_ledger::guild_id.eq(guild_id)
+                .and(audio_ledger::link_or_name.eq(name))
+                .and(audio_ledger::visibility.ne(Visibility::Private.as_str()))
+                .and(audio_ledger::downloaded.eq(true))
+            )
+            .order(audio_ledger::id.asc())
+            .first(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Guild visible sounds called `name` that don't know their guild. They're usable wherever their uploader is a member.
+pub async fn load_unscoped_audio_by_name(cfg: &DatabaseConfiguration, name: &str) -> Result<Vec<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .filter(
+                audio_ledger::guild_id.is_null()
+                .and(audio_ledger::link_or_name.eq(name))
+                .and(audio_ledger::visibility.eq(Visibility::Guild.as_str()))
+                .and(audio_ledger::downloaded.eq(true))
+            )
+            .order(audio_ledger::id.asc())
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Oldest public sound called `name`.
+pub async fn load_public_audio_by_name(cfg: &DatabaseConfiguration, name: &str) -> Result<Option<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .filter(
+                audio_ledger::link_or_name.eq(name)
+                .and(audio_ledger::visibility.eq(Visibility::Public.as_str()))
+                .and(audio_ledger::downloaded.eq(true))
+            )
+            .order(audio_ledger::id.asc())
+            .first(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Sounds `user_id` could play by name in `guild_id`, sorted by name. Sounds from before guild scoping are included
+/// regardless of where their uploader is, so those still need checking.
+pub async fn load_visible_audio(cfg: &DatabaseConfiguration, user_id: BigDecimal, guild_id: Option<BigDecimal>, uploader: Option<BigDecimal>) -> Result<Vec<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        visible_audio_by(user_id, guild_id, uploader)
+            .order((audio_ledger::link_or_name.asc(), audio_ledger::id.asc()))
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// One page of [`load_visible_audio`], along with how many sounds there are across all pages.
+pub async fn load_visible_audio_page(cfg: &DatabaseConfiguration, user_id: BigDecimal, guild_id: Option<BigDecimal>, uploader: Option<BigDecimal>, offset: i64, limit: i64) -> Result<(Vec<AudioLedgerEntry>, i64), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(total) = ({
+        visible_audio_by(user_id.clone(), guild_id.clone(), uploader.clone())
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    let Ok(val) = ({
+        visible_audio_by(user_id, guild_id, uploader)
+            .order((audio_ledger::link_or_name.asc(), audio_ledger::id.asc()))
+            .offset(offset)
+            .limit(limit)
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok((val, total))
+}
+
+fn visible_audio_by(user_id: BigDecimal, guild_id: Option<BigDecimal>, uploader: Option<BigDecimal>) -> audio_ledger::BoxedQuery<'static, Pg> {
+    let mut query = visible_audio(user_id, guild_id);
+    if let Some(uploader) = uploader {
+        query = query.filter(audio_ledger::uploader.eq(uploader));
+    }
+    query
+}
+
+fn visible_audio(user_id: BigDecimal, guild_id: Option<BigDecimal>) -> audio_ledger::BoxedQuery<'static, Pg> {
+    audio_ledger::table
+        .filter(
+            audio_ledger::uploader.eq(user_id)
+            .or(audio_ledger::visibility.eq(Visibility::Public.as_str()))
+            .or(audio_ledger::guild_id.is_null().and(audio_ledger::visibility.eq(Visibility::Guild.as_str())))
+            .or(audio_ledger::guild_id.is_not_distinct_from(guild_id).and(audio_ledger::visibility.ne(Visibility::Private.as_str())))
+        )
+        .filter(audio_ledger::downloaded.eq(true))
+        .into_boxed()
+}
+
+/// Returns whether `user_id` had a sound called `name` to rename.
+pub async fn rename_audio(cfg: &DatabaseConfiguration, user_id: BigDecimal, name: &str, new_name: &str) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(updated) = ({
+        diesel::update(
+            audio_ledger::table.filter(
+                audio_ledger::uploader.eq(user_id)
+                .and(audio_ledger::link_or_name.eq(name))
+                .and(audio_ledger::downloaded.eq(true))
+            )
+        )
+            .set(audio_ledger::link_or_name.eq(new_name))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database update failed".into()));
+    };
+
+    Ok(updated > 0)
+}
+
+pub async fn record_audio_play(cfg: &DatabaseConfiguration, id: i64) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::update(audio_ledger::table.find(id))
+            .set(audio_ledger::play_count.eq(audio_ledger::play_count + 1))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database update failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Returns whether `user_id` has a sound called `name` to change.
+pub async fn update_audio_sharing(cfg: &DatabaseConfiguration, user_id: BigDecimal, name: &str, changes: &AudioSharingChanges<'_>) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(updated) = ({
+        diesel::update(
+            audio_ledger::table.filter(
+                audio_ledger::uploader.eq(user_id)
+                .and(audio_ledger::link_or_name.eq(name))
+                .and(audio_ledger::downloaded.eq(true))
+            )
+        )
+            .set(changes)
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database update failed".into()));
+    };
+
+    Ok(updated > 0)
+}
+
+/// Every file the ledger refers to, including kept originals of transcoded sounds.
+pub async fn load_ledger_file_paths(cfg: &DatabaseConfiguration) -> Result<Vec<String>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .select((audio_ledger::file_path, audio_ledger::original_file_path))
+            .load::<(String, Option<String>)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val.into_iter().flat_map(|(file_path, original)| std::iter::once(file_path).chain(original)).collect())
+}
+
+/// Sounds not yet stored as `codec` at `sample_rate`.
+pub async fn load_audio_not_in_format(cfg: &DatabaseConfiguration, codec: &str, sample_rate: i32) -> Result<Vec<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_ledger::table
+            .filter(
+                audio_ledger::downloaded.eq(true)
+                .and(
+                    audio_ledger::codec.is_distinct_from(codec)
+                    .or(audio_ledger::sample_rate.is_distinct_from(sample_rate))
+                )
+            )
+            .order(audio_ledger::id.asc())
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Points every sound stored at `file_path` to its replacement. Several names can share one file, see [`blobs`].
+pub async fn update_audio_file(cfg: &DatabaseConfiguration, file_path: &str, changes: &AudioFileChanges<'_>) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::update(audio_ledger::table.filter(audio_ledger::file_path.eq(file_path)))
+            .set(changes)
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database update failed".into()));
+    };
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, Selectable, SelectableHelper, dsl::{count_star, max}, prelude::{Identifiable, Insertable, QueryDsl, Queryable}};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+
+use crate::schema::{audio_ledger, playlist_entries, playlists};
+
+use super::{connect, AudioLedgerEntry};
+
+#[derive(Debug)]
+#[derive(Queryable, Identifiable, Selectable)]
+#[diesel(table_name = playlists)]
+pub struct Playlist {
+    pub id: i64,
+    pub discord_user: BigDecimal,
+    pub name: String,
+}
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = playlists)]
+pub struct NewPlaylist<'a> {
+    pub discord_user: BigDecimal,
+    pub name: &'a str,
+}
+
+#[derive(Debug)]
+#[derive(Queryable, Identifiable, Selectable)]
+#[diesel(table_name = playlist_entries)]
+pub struct PlaylistEntry {
+    pub id: i64,
+    pub playlist: i64,
+    pub audio: i64,
+    pub position: i32,
+}
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = playlist_entries)]
+struct NewPlaylistEntry {
+    playlist: i64,
+    audio: i64,
+    position: i32,
+}
+
+/// Returns `None` if the user already has a playlist called that.
+pub async fn create_playlist(cfg: &DatabaseConfiguration, data: &NewPlaylist<'_>) -> Result<Option<Playlist>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        diesel::insert_into(playlists::table)
+            .values(data)
+            .on_conflict_do_nothing()
+            .returning(Playlist::as_returning())
+            .get_result(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(val)
+}
+
+pub async fn load_playlist(cfg: &DatabaseConfiguration, user_id: BigDecimal, name: &str) -> Result<Option<Playlist>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        playlists::table
+            .filter(
+                playlists::discord_user.eq(user_id)
+                .and(playlists::name.eq(name))
+            )
+            .get_result(&mut conn)
+            .await
+            .optional()
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// The user's playlists by name, with how many entries each has.
+pub async fn load_playlists(cfg: &DatabaseConfiguration, user_id: BigDecimal) -> Result<Vec<(Playlist, i64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(owned) = ({
+        playlists::table
+            .filter(playlists::discord_user.eq(user_id))
+            .order(playlists::name.asc())
+            .load::<Playlist>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    let Ok(counts) = ({
+        playlist_entries::table
+            .filter(playlist_entries::playlist.eq_any(owned.iter().map(|playlist| playlist.id).collect::<Vec<_>>()))
+            .group_by(playlist_entries::playlist)
+            .select((playlist_entries::playlist, count_star()))
+            .load::<(i64, i64)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(owned.into_iter().map(|playlist| {
+        let count = counts.iter().find(|(id, _)| *id == playlist.id).map(|(_, count)| *count).unwrap_or(0);
+        (playlist, count)
+    }).collect())
+}
+
+/// Entries in play order, along with what they play.
+pub async fn load_playlist_entries(cfg: &DatabaseConfiguration, playlist_id: i64) -> Result<Vec<(PlaylistEntry, AudioLedgerEntry)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        playlist_entries::table
+            .inner_join(audio_ledger::table)
+            .filter(playlist_entries::playlist.eq(playlist_id))
+            .order((playlist_entries::position.asc(), playlist_entries::id.asc()))
+            .select((PlaylistEntry::as_select(), AudioLedgerEntry::as_select()))
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Adds the ledger entries to the end of the playlist, in the given order.
+pub async fn append_playlist_entries(cfg: &DatabaseConfiguration, playlist_id: i64, audio_ids: &[i64]) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+            let last: Option<i32> = playlist_entries::table
+                .filter(playlist_entries::playlist.eq(playlist_id))
+                .select(max(playlist_entries::position))
+                .get_result(conn)
+                .await?;
+            let start = last.map(|last| last + 1).unwrap_or(0);
+            let entries: Vec<_> = audio_ids.iter().zip(start..).map(|(audio, position)| NewPlaylistEntry {
+                playlist: playlist_id,
+                audio: *audio,
+                position,
+            }).collect();
+            diesel::insert_into(playlist_entries::table)
+                .values(entries)
+                .execute(conn)
+                .await?;
+            Ok(())
+        }.scope_boxed()).await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Swaps out all of the playlist's entries for the given ones.
+pub async fn replace_playlist_entries(cfg: &DatabaseConfiguration, playlist_id: i64, audio_ids: &[i64]) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+            diesel::delete(playlist_entries::table.filter(playlist_entries::playlist.eq(playlist_id)))
+                .execute(conn)
+                .await?;
+            let entries: Vec<_> = audio_ids.iter().zip(0..).map(|(audio, position)| NewPlaylistEntry {
+                playlist: playlist_id,
+                audio: *audio,
+                position,
+            }).collect();
+            diesel::insert_into(playlist_entries::table)
+                .values(entries)
+                .execute(conn)
+                .await?;
+            Ok(())
+        }.scope_boxed()).await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Removes an entry, moving everything after it up by one.
+pub async fn remove_playlist_entry(cfg: &DatabaseConfiguration, entry: &PlaylistEntry) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+            diesel::delete(playlist_entries::table.find(entry.id))
+                .execute(conn)
+                .await?;
+            diesel::update(
+                playlist_entries::table.filter(
+                    playlist_entries::playlist.eq(entry.playlist)
+                    .and(playlist_entries::position.gt(entry.position))
+                )
+            )
+                .set(playlist_entries::position.eq(playlist_entries::position - 1))
+                .execute(conn)
+                .await?;
+            Ok(())
+        }.scope_boxed()).await
+    }) else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Entries go along with it.
+pub async fn delete_playlist(cfg: &DatabaseConfiguration, playlist_id: i64) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = diesel::delete(playlists::table.find(playlist_id)).execute(&mut conn).await else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(())
+}
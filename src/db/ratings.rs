@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use diesel::{BoolExpressionMethods, ExpressionMethods, dsl::now, prelude::{Insertable, QueryDsl}, upsert::excluded};
+use diesel_async::RunQueryDsl;
+
+use crate::schema::{audio_ledger, track_ratings};
+
+use super::{connect, visible_audio, AudioLedgerEntry};
+
+/// What `/like` rates a track.
+pub const LIKED_RATING: i16 = 5;
+/// Tracks rated at least this count as favorites.
+pub const FAVORITE_RATING: i16 = 4;
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = track_ratings)]
+pub struct NewTrackRating {
+    pub discord_user: BigDecimal,
+    pub audio: i64,
+    pub rating: i16,
+}
+
+/// Rates a track, replacing any earlier rating from the same user.
+pub async fn rate_audio(cfg: &DatabaseConfiguration, data: &NewTrackRating) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::insert_into(track_ratings::table)
+            .values(data)
+            .on_conflict((track_ratings::discord_user, track_ratings::audio))
+            .do_update()
+            .set((track_ratings::rating.eq(excluded(track_ratings::rating)), track_ratings::rated_at.eq(now)))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Tracks `user_id` rated at least [`FAVORITE_RATING`] that they could still play in `guild_id`, most recently rated
+/// first. Sounds go by the same rule as `/play`, so ones from before guild scoping still need checking.
+pub async fn load_favorites(cfg: &DatabaseConfiguration, user_id: BigDecimal, guild_id: Option<BigDecimal>) -> Result<Vec<(i16, AudioLedgerEntry)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let visible = visible_audio(user_id.clone(), guild_id).select(audio_ledger::id);
+    let Ok(val) = ({
+        track_ratings::table
+            .inner_join(audio_ledger::table)
+            .filter(track_ratings::discord_user.eq(user_id))
+            .filter(track_ratings::rating.ge(FAVORITE_RATING))
+            .filter(audio_ledger::downloaded.eq(false).or(audio_ledger::id.eq_any(visible)))
+            .order(track_ratings::rated_at.desc())
+            .select((track_ratings::rating, audio_ledger::all_columns))
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Every rating any of `user_ids` gave any of `tracks`, as `(user, track id, rating)`. Youtube links get a ledger row
+/// per user who added them, so a rating on any row for the same link counts for every track with that link.
+pub async fn load_ratings(cfg: &DatabaseConfiguration, user_ids: &[BigDecimal], tracks: &[AudioLedgerEntry]) -> Result<Vec<(BigDecimal, i64, i16)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let sound_ids: Vec<_> = tracks.iter().filter(|track| track.downloaded).map(|track| track.id).collect();
+    let links: Vec<_> = tracks.iter().filter(|track| !track.downloaded).map(|track| track.link_or_name.as_str()).collect();
+    let Ok(rows) = ({
+        track_ratings::table
+            .inner_join(audio_ledger::table)
+            .filter(track_ratings::discord_user.eq_any(user_ids))
+            .filter(
+                audio_ledger::id.eq_any(sound_ids)
+                .or(audio_ledger::downloaded.eq(false).and(audio_ledger::link_or_name.eq_any(links)))
+            )
+            .order(track_ratings::rated_at.asc())
+            .select((track_ratings::discord_user, audio_ledger::id, audio_ledger::downloaded, audio_ledger::link_or_name, track_ratings::rating))
+            .load::<(BigDecimal, i64, bool, String, i16)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    let mut by_link: HashMap<&str, Vec<i64>> = HashMap::new();
+    for track in tracks.iter().filter(|track| !track.downloaded) {
+        by_link.entry(track.link_or_name.as_str()).or_default().push(track.id);
+    }
+    // Later ratings win, in case someone rated the same link through more than one row.
+    let mut ratings = HashMap::new();
+    for (user, audio, downloaded, link, rating) in rows {
+        if downloaded {
+            ratings.insert((user, audio), rating);
+            continue;
+        }
+        for track in by_link.get(link.as_str()).into_iter().flatten() {
+            ratings.insert((user.clone(), *track), rating);
+        }
+    }
+
+    Ok(ratings.into_iter().map(|((user, track), rating)| (user, track, rating)).collect())
+}
@@ -0,0 +1,52 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use diesel::{ExpressionMethods, prelude::QueryDsl};
+use diesel_async::RunQueryDsl;
+
+use crate::schema::recording_opt_outs;
+
+use super::connect;
+
+pub async fn opt_out_of_recording(cfg: &DatabaseConfiguration, user_id: BigDecimal) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::insert_into(recording_opt_outs::table)
+            .values(recording_opt_outs::discord_user.eq(user_id))
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Returns whether `user_id` had opted out.
+pub async fn opt_in_to_recording(cfg: &DatabaseConfiguration, user_id: BigDecimal) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(removed) = diesel::delete(recording_opt_outs::table.find(user_id)).execute(&mut conn).await else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(removed > 0)
+}
+
+/// Which of `user_ids` don't want to be recorded.
+pub async fn load_opted_out(cfg: &DatabaseConfiguration, user_ids: &[BigDecimal]) -> Result<Vec<BigDecimal>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        recording_opt_outs::table
+            .filter(recording_opt_outs::discord_user.eq_any(user_ids))
+            .select(recording_opt_outs::discord_user)
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
@@ -0,0 +1,204 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{BoolExpressionMethods, BoxableExpression, ExpressionMethods, IntoSql, dsl::{count_star, max, sql, sum}, pg::Pg, prelude::{Insertable, QueryDsl}, sql_types::{BigInt, Bool, Date, Integer, Text}};
+use diesel_async::RunQueryDsl;
+use strum::{EnumString, IntoStaticStr};
+
+use crate::schema::play_events;
+
+use super::connect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumString, IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum PlayEventKind {
+    Start,
+    /// Stopped before reaching the end, e.g. by `/next` or `/stop`.
+    Skip,
+    Complete,
+}
+
+impl PlayEventKind {
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+}
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = play_events)]
+pub struct NewPlayEvent<'a> {
+    pub guild_id: BigDecimal,
+    pub user_id: BigDecimal,
+    pub source: &'a str,
+    pub title: &'a str,
+    pub kind: &'a str,
+    pub seconds_listened: f64,
+}
+
+/// Which plays to look at. Unset fields don't filter.
+#[derive(Debug, Default)]
+pub struct StatsScope {
+    pub guild_id: Option<BigDecimal>,
+    pub user_id: Option<BigDecimal>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+type EventFilter<'a> = Box<dyn BoxableExpression<play_events::table, Pg, SqlType = Bool> + 'a>;
+
+impl StatsScope {
+    /// A condition rather than a boxed query, since those can't be grouped.
+    fn events(&self) -> EventFilter<'_> {
+        let mut filter: EventFilter<'_> = Box::new(true.into_sql::<Bool>());
+        if let Some(guild_id) = self.guild_id.as_ref() {
+            filter = Box::new(filter.and(play_events::guild_id.eq(guild_id)));
+        }
+        if let Some(user_id) = self.user_id.as_ref() {
+            filter = Box::new(filter.and(play_events::user_id.eq(user_id)));
+        }
+        if let Some(since) = self.since {
+            filter = Box::new(filter.and(play_events::occurred_at.ge(since)));
+        }
+        if let Some(until) = self.until {
+            filter = Box::new(filter.and(play_events::occurred_at.lt(until)));
+        }
+        filter
+    }
+}
+
+pub async fn record_play_event(cfg: &DatabaseConfiguration, data: &NewPlayEvent<'_>) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = diesel::insert_into(play_events::table).values(data).execute(&mut conn).await else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Most common sources among events of `kind`, as `(source, title, count)`.
+pub async fn load_top_sources(cfg: &DatabaseConfiguration, scope: &StatsScope, kind: PlayEventKind, limit: i64) -> Result<Vec<(String, Option<String>, i64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        play_events::table
+            .filter(scope.events())
+            .filter(play_events::kind.eq(kind.as_str()))
+            .group_by(play_events::source)
+            .select((play_events::source, max(play_events::title), count_star()))
+            .order(count_star().desc())
+            .limit(limit)
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Users who started the most plays, as `(user, count)`.
+pub async fn load_top_requesters(cfg: &DatabaseConfiguration, scope: &StatsScope, limit: i64) -> Result<Vec<(BigDecimal, i64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        play_events::table
+            .filter(scope.events())
+            .filter(play_events::kind.eq(PlayEventKind::Start.as_str()))
+            .group_by(play_events::user_id)
+            .select((play_events::user_id, count_star()))
+            .order(count_star().desc())
+            .limit(limit)
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Seconds listened across finished plays, skipped or not.
+pub async fn total_seconds_listened(cfg: &DatabaseConfiguration, scope: &StatsScope) -> Result<f64, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        play_events::table
+            .filter(scope.events())
+            .filter(play_events::kind.ne(PlayEventKind::Start.as_str()))
+            .select(sum(play_events::seconds_listened))
+            .get_result::<Option<f64>>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val.unwrap_or(0.0))
+}
+
+/// How many plays were started at each hour of the day (0 to 23) in `timezone`. Hours without plays are left out.
+pub async fn count_play_starts_by_hour(cfg: &DatabaseConfiguration, scope: &StatsScope, timezone: &str) -> Result<Vec<(i32, i64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        play_events::table
+            .filter(scope.events())
+            .filter(play_events::kind.eq(PlayEventKind::Start.as_str()))
+            .select((
+                sql::<Integer>("EXTRACT(HOUR FROM occurred_at AT TIME ZONE ").bind::<Text, _>(timezone).sql(")::INT4"),
+                sql::<BigInt>("COUNT(*)"),
+            ))
+            .group_by(sql::<Integer>("1"))
+            .load::<(i32, i64)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// How many plays were started on each weekday in `timezone`, from Monday as 0 to Sunday as 6. Weekdays without
+/// plays are left out.
+pub async fn count_play_starts_by_weekday(cfg: &DatabaseConfiguration, scope: &StatsScope, timezone: &str) -> Result<Vec<(i32, i64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        play_events::table
+            .filter(scope.events())
+            .filter(play_events::kind.eq(PlayEventKind::Start.as_str()))
+            .select((
+                sql::<Integer>("EXTRACT(ISODOW FROM occurred_at AT TIME ZONE ").bind::<Text, _>(timezone).sql(")::INT4 - 1"),
+                sql::<BigInt>("COUNT(*)"),
+            ))
+            .group_by(sql::<Integer>("1"))
+            .load::<(i32, i64)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Days in `timezone` with at least one play started, oldest first.
+pub async fn load_play_days(cfg: &DatabaseConfiguration, scope: &StatsScope, timezone: &str) -> Result<Vec<NaiveDate>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        play_events::table
+            .filter(scope.events())
+            .filter(play_events::kind.eq(PlayEventKind::Start.as_str()))
+            .select(sql::<Date>("(occurred_at AT TIME ZONE ").bind::<Text, _>(timezone).sql(")::DATE"))
+            .distinct()
+            .order(sql::<Date>("1"))
+            .load::<NaiveDate>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
@@ -0,0 +1,140 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use diesel::{BoolExpressionMethods, ExpressionMethods, IntoSql, PgTextExpressionMethods, SelectableHelper, dsl::exists, pg::Pg, prelude::{Insertable, QueryDsl}, sql_types::{Float4, Text}};
+use diesel_async::RunQueryDsl;
+
+use crate::schema::{audio_ledger, audio_tags};
+
+use super::{connect, visible_audio, AudioLedgerEntry};
+
+/// Matches the `audio_tags.tag` column.
+pub const MAX_TAG_LENGTH: usize = 32;
+/// Trigram similarity needed for a name to match a search without containing it outright.
+const SIMILARITY_THRESHOLD: f32 = 0.3;
+
+diesel::define_sql_function! {
+    /// From `pg_trgm`, how alike two strings are, from 0 to 1.
+    fn similarity(a: Text, b: Text) -> Float4;
+}
+
+diesel::define_sql_function! {
+    /// From `pg_trgm`, sets how similar strings need to be for [`TrigramMatch`], for the rest of the session.
+    fn set_limit(limit: Float4) -> Float4;
+}
+
+// `pg_trgm`'s `%`, which unlike comparing `similarity` can use the trigram index.
+diesel::infix_operator!(TrigramMatch, " % ", backend: Pg);
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = audio_tags)]
+struct NewAudioTag<'a> {
+    audio: i64,
+    tag: &'a str,
+}
+
+/// Unset fields don't filter. Every tag in `tags` has to be on a sound for it to match.
+#[derive(Debug, Default)]
+pub struct SoundSearch<'a> {
+    pub name: Option<&'a str>,
+    pub tags: &'a [String],
+    pub uploader: Option<BigDecimal>,
+    pub min_duration_secs: Option<f64>,
+    pub max_duration_secs: Option<f64>,
+}
+
+/// Adds whichever of `tags` the sound didn't already have.
+pub async fn add_tags(cfg: &DatabaseConfiguration, audio: i64, tags: &[String]) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let rows: Vec<_> = tags.iter().map(|tag| NewAudioTag { audio, tag: tag.as_str() }).collect();
+    let Ok(_) = ({
+        diesel::insert_into(audio_tags::table)
+            .values(&rows)
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Returns how many of `tags` the sound actually had.
+pub async fn remove_tags(cfg: &DatabaseConfiguration, audio: i64, tags: &[String]) -> Result<usize, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(removed) = ({
+        diesel::delete(audio_tags::table.filter(audio_tags::audio.eq(audio).and(audio_tags::tag.eq_any(tags))))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(removed)
+}
+
+pub async fn load_tags(cfg: &DatabaseConfiguration, audio: i64) -> Result<Vec<String>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        audio_tags::table
+            .filter(audio_tags::audio.eq(audio))
+            .select(audio_tags::tag)
+            .order(audio_tags::tag.asc())
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Sounds `user_id` could play in `guild_id` that match `search`, best name matches first. Like
+/// [`super::load_visible_audio`], sounds from before guild scoping still need checking.
+pub async fn search_audio(cfg: &DatabaseConfiguration, user_id: BigDecimal, guild_id: Option<BigDecimal>, search: &SoundSearch<'_>, limit: i64) -> Result<Vec<AudioLedgerEntry>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let name = search.name.unwrap_or_default().to_owned();
+    let mut query = visible_audio(user_id, guild_id);
+    if !name.is_empty() {
+        let Ok(_) = diesel::select(set_limit(SIMILARITY_THRESHOLD)).get_result::<f32>(&mut conn).await else {
+            return Err(RequestError::User("Database query failed".into()));
+        };
+        let pattern = format!("%{}%", name.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        query = query.filter(
+            audio_ledger::link_or_name.ilike(pattern)
+            .or(TrigramMatch::new(audio_ledger::link_or_name, name.clone().into_sql::<Text>()))
+        );
+    }
+    for tag in search.tags.iter() {
+        query = query.filter(exists(
+            audio_tags::table.filter(audio_tags::audio.eq(audio_ledger::id).and(audio_tags::tag.eq(tag.clone())))
+        ));
+    }
+    if let Some(uploader) = search.uploader.clone() {
+        query = query.filter(audio_ledger::uploader.eq(uploader));
+    }
+    if let Some(min) = search.min_duration_secs {
+        query = query.filter(audio_ledger::duration_secs.ge(min));
+    }
+    if let Some(max) = search.max_duration_secs {
+        query = query.filter(audio_ledger::duration_secs.le(max));
+    }
+
+    let Ok(val) = ({
+        query
+            .order((similarity(audio_ledger::link_or_name, name).desc(), audio_ledger::play_count.desc(), audio_ledger::link_or_name.asc()))
+            .select(AudioLedgerEntry::as_select())
+            .limit(limit)
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
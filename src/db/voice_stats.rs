@@ -0,0 +1,159 @@
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use diesel::{BoolExpressionMethods, BoxableExpression, ExpressionMethods, IntoSql, dsl::sum, pg::Pg, prelude::{Insertable, QueryDsl}, sql_types::Bool, upsert::excluded};
+use diesel_async::RunQueryDsl;
+
+use crate::schema::{speaking_time, voice_stats_opt_ins};
+
+use super::connect;
+
+#[derive(Debug)]
+#[derive(Insertable)]
+#[diesel(table_name = speaking_time)]
+pub struct NewSpeakingTime {
+    pub guild_id: BigDecimal,
+    pub discord_user: BigDecimal,
+    pub day: NaiveDate,
+    pub seconds_spoken: f64,
+}
+
+/// Which speaking time to look at. Unset fields don't filter.
+#[derive(Debug, Default)]
+pub struct SpeakingTimeScope {
+    pub guild_id: Option<BigDecimal>,
+    pub user_id: Option<BigDecimal>,
+    pub since: Option<NaiveDate>,
+}
+
+type RowFilter<'a> = Box<dyn BoxableExpression<speaking_time::table, Pg, SqlType = Bool> + 'a>;
+
+impl SpeakingTimeScope {
+    /// A condition rather than a boxed query, since those can't be grouped.
+    fn rows(&self) -> RowFilter<'_> {
+        let mut filter: RowFilter<'_> = Box::new(true.into_sql::<Bool>());
+        if let Some(guild_id) = self.guild_id.as_ref() {
+            filter = Box::new(filter.and(speaking_time::guild_id.eq(guild_id)));
+        }
+        if let Some(user_id) = self.user_id.as_ref() {
+            filter = Box::new(filter.and(speaking_time::discord_user.eq(user_id)));
+        }
+        if let Some(since) = self.since {
+            filter = Box::new(filter.and(speaking_time::day.ge(since)));
+        }
+        filter
+    }
+}
+
+/// Returns whether `user_id` wasn't opted in already.
+pub async fn opt_in_to_voice_stats(cfg: &DatabaseConfiguration, user_id: BigDecimal) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(inserted) = ({
+        diesel::insert_into(voice_stats_opt_ins::table)
+            .values(voice_stats_opt_ins::discord_user.eq(user_id))
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(inserted > 0)
+}
+
+/// Returns whether `user_id` had opted in. What was already counted stays until purged.
+pub async fn opt_out_of_voice_stats(cfg: &DatabaseConfiguration, user_id: BigDecimal) -> Result<bool, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(removed) = diesel::delete(voice_stats_opt_ins::table.find(user_id)).execute(&mut conn).await else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(removed > 0)
+}
+
+/// Which of `user_ids` want their speaking time counted.
+pub async fn load_opted_in(cfg: &DatabaseConfiguration, user_ids: &[BigDecimal]) -> Result<Vec<BigDecimal>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        voice_stats_opt_ins::table
+            .filter(voice_stats_opt_ins::discord_user.eq_any(user_ids))
+            .select(voice_stats_opt_ins::discord_user)
+            .load(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val)
+}
+
+/// Adds onto whatever was already counted for the same guild, user and day.
+pub async fn add_speaking_time(cfg: &DatabaseConfiguration, rows: &[NewSpeakingTime]) -> Result<(), RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(_) = ({
+        diesel::insert_into(speaking_time::table)
+            .values(rows)
+            .on_conflict((speaking_time::guild_id, speaking_time::discord_user, speaking_time::day))
+            .do_update()
+            .set(speaking_time::seconds_spoken.eq(speaking_time::seconds_spoken + excluded(speaking_time::seconds_spoken)))
+            .execute(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database insert failed".into()));
+    };
+
+    Ok(())
+}
+
+/// Seconds spoken per day, oldest first. Days without any speaking are left out.
+pub async fn load_daily_speaking_time(cfg: &DatabaseConfiguration, scope: &SpeakingTimeScope) -> Result<Vec<(NaiveDate, f64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        speaking_time::table.filter(scope.rows())
+            .group_by(speaking_time::day)
+            .select((speaking_time::day, sum(speaking_time::seconds_spoken)))
+            .order(speaking_time::day.asc())
+            .load::<(NaiveDate, Option<f64>)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val.into_iter().map(|(day, seconds)| (day, seconds.unwrap_or_default())).collect())
+}
+
+/// Users who spoke the most, as `(user, seconds)`. Those who since opted out are left out.
+pub async fn load_top_speakers(cfg: &DatabaseConfiguration, scope: &SpeakingTimeScope, limit: i64) -> Result<Vec<(BigDecimal, f64)>, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(val) = ({
+        speaking_time::table.filter(scope.rows())
+            .filter(speaking_time::discord_user.eq_any(voice_stats_opt_ins::table.select(voice_stats_opt_ins::discord_user)))
+            .group_by(speaking_time::discord_user)
+            .select((speaking_time::discord_user, sum(speaking_time::seconds_spoken)))
+            .order(sum(speaking_time::seconds_spoken).desc())
+            .limit(limit)
+            .load::<(BigDecimal, Option<f64>)>(&mut conn)
+            .await
+    }) else {
+        return Err(RequestError::User("Database query failed".into()));
+    };
+
+    Ok(val.into_iter().map(|(user, seconds)| (user, seconds.unwrap_or_default())).collect())
+}
+
+/// Forgets everything counted for `user_id`, in every guild. Returns how many days were removed.
+pub async fn purge_speaking_time(cfg: &DatabaseConfiguration, user_id: BigDecimal) -> Result<usize, RequestError> {
+    let mut conn = connect(cfg).await?;
+
+    let Ok(removed) = diesel::delete(speaking_time::table.filter(speaking_time::discord_user.eq(user_id))).execute(&mut conn).await else {
+        return Err(RequestError::User("Database delete failed".into()));
+    };
+
+    Ok(removed)
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, process::Stdio, sync::{Arc, Mutex}};
+
+use azel::DatabaseConfiguration;
+use serenity::{all::Context, prelude::TypeMapKey};
+use tokio::{io::{AsyncBufReadExt, BufReader}, sync::{watch, Semaphore}};
+use tracing as trc;
+
+use crate::{cache::CacheManager, limits::format_duration, settings::DownloadSettings};
+
+/// Prefix for the progress lines we ask yt-dlp to print, so they can be told apart from everything else.
+const PROGRESS_MARKER: &str = "[yamble-progress]";
+/// Prefix of in progress download directories. Anything with it left over on startup was interrupted.
+pub const STAGING_PREFIX: &str = ".partial-";
+
+pub struct DownloadManagerKey;
+
+impl TypeMapKey for DownloadManagerKey {
+    type Value = Arc<DownloadManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<DownloadManager>> {
+    ctx.data.read().await.get::<DownloadManagerKey>().cloned()
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadJob {
+    pub url: String,
+    pub video_id: String,
+    pub destination: PathBuf,
+    pub max_filesize_bytes: Option<u64>,
+    pub ytdlp_path: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadStatus {
+    Queued,
+    Running {
+        percent: f32,
+        eta_secs: Option<u64>,
+    },
+    Finished,
+    Failed(String),
+}
+
+impl DownloadStatus {
+    pub fn is_done(&self) -> bool {
+        matches!(self, DownloadStatus::Finished | DownloadStatus::Failed(_))
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            DownloadStatus::Queued => "Waiting for a download slot...".to_owned(),
+            DownloadStatus::Running { percent, eta_secs: Some(eta) } => format!("Downloading for replay: {percent:.1}% (ETA {})", format_duration(*eta)),
+            DownloadStatus::Running { percent, eta_secs: None } => format!("Downloading for replay: {percent:.1}%"),
+            DownloadStatus::Finished => "Saved for replay.".to_owned(),
+            DownloadStatus::Failed(reason) => format!("Couldn't save this for replay: {reason}"),
+        }
+    }
+}
+
+/// Runs yt-dlp downloads in the background, at most `workers` at a time.
+///
+/// Each job reports progress through a [`watch`] channel, so callers can surface it however they like.
+/// Jobs are deduplicated by video id: submitting a video that's already downloading just subscribes to the
+/// running job. Downloads land in a temporary directory next to the destination and only get renamed into
+/// place once complete, so the destination existing always means a finished download.
+/// Finished downloads are handed to the [`CacheManager`].
+#[derive(Debug)]
+pub struct DownloadManager {
+    workers: Semaphore,
+    cache: Arc<CacheManager>,
+    in_flight: Mutex<HashMap<String, watch::Receiver<DownloadStatus>>>,
+}
+
+impl DownloadManager {
+    pub fn new(settings: &DownloadSettings, cache: Arc<CacheManager>) -> Self {
+        Self {
+            workers: Semaphore::new(settings.workers.max(1)),
+            cache,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn submit(self: &Arc<Self>, db_cfg: Arc<DatabaseConfiguration>, job: DownloadJob) -> watch::Receiver<DownloadStatus> {
+        let mut in_flight = self.in_flight.lock().expect("download registry not poisoned");
+        if let Some(existing) = in_flight.get(job.video_id.as_str()) {
+            trc::info!("VIDEO-DOWNLOAD-JOIN {:?}", job.video_id);
+            return existing.clone();
+        }
+
+        let (progress, receiver) = watch::channel(DownloadStatus::Queued);
+        in_flight.insert(job.video_id.clone(), receiver.clone());
+        drop(in_flight);
+
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let status = match manager.run_staged(&job, &progress).await {
+                Ok(()) => {
+                    if let Err(e) = manager.cache.mark_played(&db_cfg, job.video_id.as_str()).await {
+                        trc::error!("cache update failed {e:?}");
+                    }
+                    DownloadStatus::Finished
+                },
+                Err(reason) => {
+                    trc::error!("VIDEO-DOWNLOAD-FAIL {:?} {reason}", job.video_id);
+                    DownloadStatus::Failed(reason)
+                },
+            };
+            // Unregister before the final status goes out, so anyone reacting to it by resubmitting gets a fresh job.
+            manager.in_flight.lock().expect("download registry not poisoned").remove(job.video_id.as_str());
+            progress.send_replace(status);
+        });
+
+        receiver
+    }
+
+    /// Downloads into a staging directory, then moves it to the job's destination.
+    async fn run_staged(&self, job: &DownloadJob, progress: &watch::Sender<DownloadStatus>) -> Result<(), String> {
+        let staging = staging_dir(job.destination.as_path());
+        let result = self.run(job, staging.as_path(), progress).await
+            .and_then(|_| {
+                if std::fs::exists(job.destination.as_path()).unwrap_or(false) {
+                    // Someone else (e.g. a previous run of the bot) already finished it, keep theirs.
+                    trc::info!("VIDEO-DOWNLOAD-DUPLICATE {:?}", job.video_id);
+                    return Ok(());
+                }
+                std::fs::rename(staging.as_path(), job.destination.as_path()).map_err(|e| format!("couldn't save the download ({e})"))
+            });
+
+        match std::fs::remove_dir_all(staging.as_path()) {
+            Ok(_) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => trc::warn!("VIDEO-DOWNLOAD-CLEANUP-FAIL {staging:?} {e:?}"),
+        }
+
+        result
+    }
+
+    async fn run(&self, job: &DownloadJob, staging: &Path, progress: &watch::Sender<DownloadStatus>) -> Result<(), String> {
+        let _permit = self.workers.acquire().await.map_err(|_e| "downloads are shutting down".to_owned())?;
+        trc::info!("VIDEO-DOWNLOAD-START {:?}", job.video_id);
+        progress.send_replace(DownloadStatus::Running { percent: 0.0, eta_secs: None });
+
+        let mut command = tokio::process::Command::new(job.ytdlp_path);
+        command
+            .arg(job.url.as_str())
+            .args(["--no-playlist", "--socket-timeout", "15", "--format", "ba"])
+            .args(["--newline", "--no-colors", "--progress-template"])
+            .arg(format!("download:{PROGRESS_MARKER} %(progress._percent_str)s %(progress.eta)s"))
+            .arg("--paths")
+            .arg(staging.as_os_str())
+            .args(["--output", "%(id)s.%(ext)s"]);
+        if let Some(max) = job.max_filesize_bytes {
+            // Metadata sizes are estimates, so have ytdlp enforce it too.
+            command.arg("--max-filesize").arg(max.to_string());
+        }
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("couldn't start yt-dlp ({e})"))?;
+
+        let stderr = child.stderr.take().expect("stderr piped");
+        let last_error = tokio::spawn(async move {
+            let mut last_error = None;
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(error) = line.strip_prefix("ERROR:") {
+                    last_error = Some(error.trim().to_owned());
+                }
+            }
+            last_error
+        });
+
+        let mut too_large = false;
+        let mut lines = BufReader::new(child.stdout.take().expect("stdout piped")).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(status) = parse_progress(line.as_str()) {
+                progress.send_replace(status);
+            } else if line.contains("larger than max-filesize") {
+                too_large = true;
+            }
+        }
+
+        let exit = child.wait().await.map_err(|e| format!("yt-dlp didn't finish ({e})"))?;
+        let last_error = last_error.await.ok().flatten();
+        if !exit.success() {
+            return Err(last_error.unwrap_or_else(|| format!("yt-dlp exited with {exit}")));
+        }
+        if too_large {
+            return Err("it's larger than the size limit".to_owned());
+        }
+
+        trc::info!("VIDEO-DOWNLOAD-END {:?}", job.video_id);
+        Ok(())
+    }
+}
+
+/// Hidden sibling of the destination, so the final rename stays on the same filesystem.
+pub fn staging_dir(destination: &Path) -> PathBuf {
+    let name = destination.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    destination.with_file_name(format!("{STAGING_PREFIX}{name}-{}", uuid::Uuid::new_v4()))
+}
+
+fn parse_progress(line: &str) -> Option<DownloadStatus> {
+    let mut parts = line.strip_prefix(PROGRESS_MARKER)?.split_whitespace();
+    let percent = parts.next()?.trim_end_matches('%').parse().ok()?;
+    let eta_secs = parts.next().and_then(|eta| eta.parse::<f64>().ok()).map(|eta| eta as u64);
+    Some(DownloadStatus::Running { percent, eta_secs })
+}
@@ -0,0 +1,159 @@
+use std::{collections::{HashMap, HashSet}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
+
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use serenity::{all::{Context, GuildId}, prelude::TypeMapKey};
+use songbird::{events::context_data::VoiceTick, model::SpeakingState, tracks::TrackQueue, Call, CoreEvent, Event, EventContext, EventHandler};
+use tracing as trc;
+
+use crate::{async_trait, db::guilds, settings};
+
+/// Voice ticks come every 20ms.
+const TICK_MS: u64 = 20;
+
+pub struct DuckManagerKey;
+
+impl TypeMapKey for DuckManagerKey {
+    type Value = Arc<DuckManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<DuckManager>> {
+    ctx.data.read().await.get::<DuckManagerKey>().cloned()
+}
+
+/// Turns the music down while people talk, for guilds that enabled it.
+#[derive(Default)]
+pub struct DuckManager {
+    duckers: Mutex<HashMap<GuildId, Arc<Ducker>>>,
+}
+
+impl DuckManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts ducking the call's music down to `ducked_volume` (0 to 1), or just changes how far down if already
+    /// ducking.
+    pub fn listen(&self, guild_id: GuildId, call: &mut Call, ducked_volume: f32) {
+        let mut duckers = self.duckers.lock().expect("not poisoned");
+        // Receivers keep their ducker alive, so only the map holding it means the call it was on is gone.
+        if let Some(ducker) = duckers.get(&guild_id).filter(|ducker| Arc::strong_count(ducker) > 1) {
+            ducker.state.lock().expect("not poisoned").ducked_volume = ducked_volume;
+            return;
+        }
+
+        let ducker = Arc::new(Ducker::new(call.queue().clone(), ducked_volume));
+        let receiver = DuckReceiver(Arc::clone(&ducker));
+        call.add_global_event(CoreEvent::SpeakingStateUpdate.into(), receiver.clone());
+        call.add_global_event(CoreEvent::VoiceTick.into(), receiver);
+        duckers.insert(guild_id, ducker);
+        trc::info!("DUCK-LISTEN {guild_id}");
+    }
+
+    /// Stops ducking, putting the music back to full volume.
+    pub fn stop(&self, guild_id: GuildId) {
+        if let Some(ducker) = self.duckers.lock().expect("not poisoned").remove(&guild_id) {
+            ducker.stopped.store(true, Ordering::Relaxed);
+            if let Some(track) = ducker.queue.current() {
+                // Nothing to restore if the track already ended.
+                let _ = track.set_volume(1.0);
+            }
+            trc::info!("DUCK-STOP {guild_id}");
+        }
+    }
+}
+
+/// Starts ducking if the guild enabled it.
+pub async fn listen_if_enabled(ctx: &Context, cfg: &DatabaseConfiguration, guild_id: GuildId, call: &tokio::sync::Mutex<Call>) -> Result<(), RequestError> {
+    let guild_settings = guilds::load_guild_settings(cfg, u64::from(guild_id).into()).await?;
+    if !guild_settings.ducking_enabled {
+        return Ok(());
+    }
+    let ducking = get(ctx).await.expect("duck manager initialized");
+    ducking.listen(guild_id, &mut *call.lock().await, volume_from_percent(guild_settings.ducking_volume));
+    Ok(())
+}
+
+pub fn volume_from_percent(percent: i16) -> f32 {
+    f32::from(percent.clamp(0, 100)) / 100.0
+}
+
+struct Ducker {
+    queue: TrackQueue,
+    stopped: AtomicBool,
+    state: Mutex<DuckState>,
+}
+
+struct DuckState {
+    ducked_volume: f32,
+    /// Where the ramp is at right now.
+    volume: f32,
+    quiet_ms: u64,
+    /// SSRCs sending something other than a microphone, like stream audio, which shouldn't duck anything.
+    ignored: HashSet<u32>,
+}
+
+impl Ducker {
+    fn new(queue: TrackQueue, ducked_volume: f32) -> Self {
+        Self {
+            queue,
+            stopped: AtomicBool::new(false),
+            state: Mutex::new(DuckState {
+                ducked_volume,
+                volume: 1.0,
+                quiet_ms: u64::MAX,
+                ignored: HashSet::new(),
+            }),
+        }
+    }
+
+    /// Moves the volume one tick's worth towards where it should be.
+    fn tick(&self, tick: &VoiceTick) {
+        let ducking_settings = &settings::get().ducking;
+        let mut state = self.state.lock().expect("not poisoned");
+
+        let talking = tick.speaking.iter().any(|(ssrc, data)| data.packet.is_some() && !state.ignored.contains(ssrc));
+        state.quiet_ms = if talking { 0 } else { state.quiet_ms.saturating_add(TICK_MS) };
+
+        let (target, ramp_ms) = if state.quiet_ms <= ducking_settings.hold_ms {
+            (state.ducked_volume, ducking_settings.attack_ms)
+        } else {
+            (1.0, ducking_settings.release_ms)
+        };
+        let step = TICK_MS as f32 / ramp_ms.max(1) as f32;
+        let previous = state.volume;
+        state.volume = if previous > target { (previous - step).max(target) } else { (previous + step).min(target) };
+
+        // Keep applying while down, a track that just started comes in at full volume.
+        if state.volume < 1.0 || state.volume != previous {
+            if let Some(track) = self.queue.current() {
+                // Fails only if the track just ended, the next tick gets the next one.
+                let _ = track.set_volume(state.volume);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct DuckReceiver(Arc<Ducker>);
+
+#[async_trait]
+impl EventHandler for DuckReceiver {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.0.stopped.load(Ordering::Relaxed) {
+            return Some(Event::Cancel);
+        }
+        match ctx {
+            EventContext::SpeakingStateUpdate(speaking) => {
+                let mut state = self.0.state.lock().expect("not poisoned");
+                if speaking.speaking.contains(SpeakingState::MICROPHONE) {
+                    state.ignored.remove(&speaking.ssrc);
+                } else {
+                    state.ignored.insert(speaking.ssrc);
+                }
+            },
+            EventContext::VoiceTick(tick) => self.0.tick(tick),
+            _ => {},
+        }
+        None
+    }
+}
@@ -0,0 +1,158 @@
+use azel::cmd::RequestError;
+use serenity::all::GuildId;
+
+use crate::settings::{self, LimitSettings};
+
+/// Most characters Discord takes in a message.
+pub const MESSAGE_LENGTH: usize = 2000;
+/// Most characters Discord takes in an embed field value.
+pub const EMBED_FIELD_LENGTH: usize = 1024;
+/// Titles and names get cut to this when listed, so one long one can't crowd out the rest.
+pub const LISTED_NAME_LENGTH: usize = 100;
+/// Room kept free for the "...and N more." line.
+const MORE_LINE_LENGTH: usize = 24;
+
+/// Effective duration/size limits for a request, after folding in any guild specific override.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_duration_secs: Option<u64>,
+    pub max_filesize_bytes: Option<u64>,
+}
+
+impl Limits {
+    pub fn for_guild(guild_id: Option<GuildId>) -> Self {
+        Self::resolve(&settings::get().limits, guild_id)
+    }
+
+    /// Guild limits, further tightened by the upload specific caps.
+    pub fn for_upload(guild_id: Option<GuildId>) -> Self {
+        let uploads = &settings::get().uploads;
+        let limits = Self::for_guild(guild_id);
+        Self {
+            max_duration_secs: tighten(limits.max_duration_secs, (uploads.max_duration_secs != 0).then_some(uploads.max_duration_secs)),
+            max_filesize_bytes: tighten(limits.max_filesize_bytes, (uploads.max_filesize_bytes != 0).then_some(uploads.max_filesize_bytes)),
+        }
+    }
+
+    fn resolve(settings: &LimitSettings, guild_id: Option<GuildId>) -> Self {
+        let nonzero = |v: u64| (v != 0).then_some(v);
+        let mut limits = Self {
+            max_duration_secs: nonzero(settings.max_duration_secs),
+            max_filesize_bytes: nonzero(settings.max_filesize_bytes),
+        };
+
+        let guild_override = guild_id.and_then(|id| settings.guilds.iter().find(|g| g.guild == u64::from(id)));
+        if let Some(guild) = guild_override {
+            limits.max_duration_secs = tighten(limits.max_duration_secs, guild.max_duration_secs.and_then(nonzero));
+            limits.max_filesize_bytes = tighten(limits.max_filesize_bytes, guild.max_filesize_bytes.and_then(nonzero));
+        }
+
+        limits
+    }
+
+    /// Unknown durations are refused when a duration limit is set, since that's usually a livestream.
+    pub fn check_duration(&self, duration_secs: Option<f64>) -> Result<(), RequestError> {
+        let Some(max) = self.max_duration_secs else {
+            return Ok(());
+        };
+        match duration_secs {
+            Some(d) if d <= max as f64 => Ok(()),
+            Some(d) => Err(RequestError::User(format!(
+                "That's {} long, but the limit here is {}.",
+                format_duration(d as u64),
+                format_duration(max),
+            ).into())),
+            None => Err(RequestError::User(format!(
+                "Couldn't figure out how long that is (livestreams aren't supported). The limit here is {}.",
+                format_duration(max),
+            ).into())),
+        }
+    }
+
+    /// Unknown sizes are let through, the duration limit is what usually catches those.
+    pub fn check_filesize(&self, filesize_bytes: Option<u64>) -> Result<(), RequestError> {
+        match (self.max_filesize_bytes, filesize_bytes) {
+            (Some(max), Some(size)) if size > max => Err(RequestError::User(format!(
+                "That's about {} MiB, but the limit here is {} MiB.",
+                size / (1024 * 1024),
+                max / (1024 * 1024),
+            ).into())),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn tighten(global: Option<u64>, guild: Option<u64>) -> Option<u64> {
+    match (global, guild) {
+        (Some(g), Some(o)) => Some(g.min(o)),
+        (g, o) => g.or(o),
+    }
+}
+
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// `s` cut to at most `max_chars` characters, with an ellipsis if anything was cut.
+pub fn shorten(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars.saturating_sub(1)) {
+        Some((end, _)) if s[end..].chars().nth(1).is_some() => format!("{}…", &s[..end]),
+        _ => s.to_owned(),
+    }
+}
+
+/// `s` with the characters Discord treats as markdown escaped, so a name shows up as written.
+pub fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// One line per entry for as many of `lines` as fit in `max_chars`, followed by how many didn't make it. `total`
+/// is how many entries there are in all, which may be more than were given.
+pub fn fit_lines(lines: Vec<String>, total: usize, max_chars: usize) -> String {
+    let mut msg = String::new();
+    let mut used = 0;
+    let mut listed = 0;
+    for line in lines {
+        let length = line.chars().count() + 1;
+        if used + length > max_chars.saturating_sub(MORE_LINE_LENGTH) {
+            break;
+        }
+        msg.push_str(&line);
+        msg.push('\n');
+        used += length;
+        listed += 1;
+    }
+    if total > listed {
+        msg.push_str(&format!("...and {} more.", total - listed));
+    }
+    msg
+}
+
+/// `msg` followed by `heading` and a bullet per item, listing as many items as still fit in a message.
+pub fn with_list(msg: String, heading: &str, items: Vec<String>) -> String {
+    with_list_within(msg, heading, items, MESSAGE_LENGTH)
+}
+
+/// [`with_list`], but stopping at `max_chars` in all so later lists in the same message still have room.
+pub fn with_list_within(mut msg: String, heading: &str, items: Vec<String>, max_chars: usize) -> String {
+    msg.push('\n');
+    msg.push_str(heading);
+    msg.push('\n');
+    let total = items.len();
+    let lines = items.into_iter().map(|item| format!("- {item}")).collect();
+    let room = max_chars.saturating_sub(msg.chars().count());
+    msg.push_str(&fit_lines(lines, total, room));
+    msg
+}
@@ -1,18 +1,49 @@
 mod cmd;
 mod audio;
+mod cache;
+mod clip;
+mod download;
+mod duck;
+mod limits;
+mod now_playing;
+mod playback;
+mod probe;
+mod record;
+mod settings;
+mod storage;
+mod transcode;
+mod voice_stats;
+mod ytdlp;
 
 mod schema;
 mod db;
 
-use std::num::NonZeroUsize;
+use std::{num::NonZeroUsize, sync::Arc};
 
 use tracing as trc;
 use serenity::async_trait;
-use songbird::{driver::SampleRate, SerenityInit};
+use songbird::{driver::{Channels, DecodeMode, SampleRate}, SerenityInit};
 
 #[tokio::main]
 async fn main() {
     let cfg = azel::setup_default_log_and_load_configuration().unwrap();
+    let settings = settings::load().expect("yamble settings to be valid");
+
+    let ytdlp = Arc::new(ytdlp::YtdlpManager::new(&settings.ytdlp));
+    if let Err(e) = ytdlp.initialize().await {
+        // Uploaded sounds still work without it, so keep going.
+        trc::error!("YTDLP-LOAD-FAIL {e:?}");
+    }
+
+    let cache = Arc::new(cache::CacheManager::new(&settings.cache));
+    cache.remove_stale_downloads();
+    let downloads = Arc::new(download::DownloadManager::new(&settings.downloads, Arc::clone(&cache)));
+    let recordings = Arc::new(record::RecordingManager::new());
+    let clips = Arc::new(clip::ClipManager::new());
+    let ducking = Arc::new(duck::DuckManager::new());
+    let voice_stats = Arc::new(voice_stats::VoiceStatsManager::new());
+    let now_playing = Arc::new(now_playing::NowPlayingManager::new());
+    let playback = Arc::new(playback::PlaybackManager::new());
 
     let mut discord = azel::build_client(
         cfg,
@@ -20,8 +51,21 @@ async fn main() {
         |b| b.register_songbird_from_config(songbird::Config::default()
             .playout_buffer_length(NonZeroUsize::new(50).unwrap())
             .playout_spike_length(10)
+            // Recordings and clips turn decoding on while they need it, see `record::set_decoding`. They rely on
+            // getting 16kHz mono, see `record::SAMPLE_RATE`.
+            .decode_mode(DecodeMode::Pass)
             .decode_sample_rate(SampleRate::Hz16000)
+            .decode_channels(Channels::Mono)
         )
+            .type_map_insert::<cache::CacheManagerKey>(Arc::clone(&cache))
+            .type_map_insert::<download::DownloadManagerKey>(Arc::clone(&downloads))
+            .type_map_insert::<ytdlp::YtdlpManagerKey>(Arc::clone(&ytdlp))
+            .type_map_insert::<record::RecordingManagerKey>(Arc::clone(&recordings))
+            .type_map_insert::<clip::ClipManagerKey>(Arc::clone(&clips))
+            .type_map_insert::<duck::DuckManagerKey>(Arc::clone(&ducking))
+            .type_map_insert::<voice_stats::VoiceStatsManagerKey>(Arc::clone(&voice_stats))
+            .type_map_insert::<now_playing::NowPlayingManagerKey>(Arc::clone(&now_playing))
+            .type_map_insert::<playback::PlaybackManagerKey>(Arc::clone(&playback))
     ).await.expect("client to be built");
 
 
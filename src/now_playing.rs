@@ -0,0 +1,193 @@
+use std::{collections::HashMap, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
+
+use azel::{discord::ExecutionContext, DatabaseConfiguration};
+use bigdecimal::ToPrimitive;
+use serenity::{all::{ChannelId, Context, CreateAllowedMentions, CreateEmbed, CreateMessage, EditMessage, GetMessages, GuildId, Http, Mention, MessageId}, prelude::TypeMapKey};
+use songbird::{tracks::{PlayMode, TrackHandle}, Event, EventContext, EventHandler, Songbird};
+use tracing as trc;
+
+use crate::{async_trait, audio::{track_info, TrackInfo}, db::{self, guilds::{self, NowPlayingMode}}, limits::format_duration};
+
+pub struct NowPlayingManagerKey;
+
+impl TypeMapKey for NowPlayingManagerKey {
+    type Value = Arc<NowPlayingManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<NowPlayingManager>> {
+    ctx.data.read().await.get::<NowPlayingManagerKey>().cloned()
+}
+
+/// The latest "Now playing" message of each guild, so there's only ever one of them around.
+#[derive(Default)]
+pub struct NowPlayingManager {
+    latest: Mutex<HashMap<GuildId, (ChannelId, MessageId)>>,
+}
+
+impl NowPlayingManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Edits the previous announcement if nothing was said since, otherwise replaces it with a new one at the bottom.
+    async fn announce(&self, http: &Http, guild_id: GuildId, channel: ChannelId, mode: NowPlayingMode, info: &TrackInfo) {
+        let (content, embed) = match mode {
+            NowPlayingMode::Off => return,
+            NowPlayingMode::Compact => (
+                format!("▶️ Now playing {}, requested by {}", info.display(), Mention::User(info.requester)),
+                None,
+            ),
+            NowPlayingMode::Embed => (String::new(), Some(embed(info))),
+        };
+
+        let previous = self.latest.lock().expect("not poisoned").remove(&guild_id);
+        if let Some((previous_channel, previous_message)) = previous {
+            if previous_channel == channel && is_latest(http, channel, previous_message).await {
+                let edit = EditMessage::new()
+                    .content(content.as_str())
+                    .embeds(embed.clone().into_iter().collect())
+                    .allowed_mentions(CreateAllowedMentions::new());
+                match channel.edit_message(http, previous_message, edit).await {
+                    Ok(_) => {
+                        self.latest.lock().expect("not poisoned").insert(guild_id, (channel, previous_message));
+                        return;
+                    },
+                    Err(e) => trc::warn!("NOW-PLAYING-EDIT-FAIL {guild_id} {e:?}"),
+                }
+            } else if let Err(e) = previous_channel.delete_message(http, previous_message).await {
+                // Someone may have deleted it already.
+                trc::warn!("NOW-PLAYING-CLEANUP-FAIL {guild_id} {e:?}");
+            }
+        }
+
+        // Announcing every track shouldn't ping the requester every time.
+        let mut msg = CreateMessage::new().allowed_mentions(CreateAllowedMentions::new());
+        if !content.is_empty() {
+            msg = msg.content(content);
+        }
+        if let Some(embed) = embed {
+            msg = msg.embed(embed);
+        }
+        match channel.send_message(http, msg).await {
+            Ok(sent) => {
+                self.latest.lock().expect("not poisoned").insert(guild_id, (channel, sent.id));
+            },
+            Err(e) => trc::warn!("NOW-PLAYING-POST-FAIL {guild_id} {e:?}"),
+        }
+    }
+
+    /// Deletes the guild's latest announcement, if there is one.
+    pub async fn clear(&self, http: &Http, guild_id: GuildId) {
+        let previous = self.latest.lock().expect("not poisoned").remove(&guild_id);
+        if let Some((channel, message)) = previous {
+            if let Err(e) = channel.delete_message(http, message).await {
+                trc::warn!("NOW-PLAYING-CLEANUP-FAIL {guild_id} {e:?}");
+            }
+        }
+    }
+}
+
+async fn is_latest(http: &Http, channel: ChannelId, message: MessageId) -> bool {
+    match channel.messages(http, GetMessages::new().limit(1)).await {
+        Ok(messages) => messages.first().is_some_and(|latest| latest.id == message),
+        Err(_) => false,
+    }
+}
+
+fn embed(info: &TrackInfo) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title("Now playing")
+        .description(info.display())
+        .field("Requested by", format!("{}", Mention::User(info.requester)), true);
+    if let Some(duration) = info.duration_secs {
+        embed = embed.field("Length", format_duration(duration as u64), true);
+    }
+    if let Some(thumbnail_url) = info.thumbnail_url.as_deref() {
+        embed = embed.thumbnail(thumbnail_url);
+    }
+    embed
+}
+
+/// Announces a track when it starts, in whatever way the guild has set, and takes the announcement down if the
+/// queue ends with it. Register for `Play` and `End`.
+#[derive(Clone)]
+pub struct NowPlayingAnnouncer {
+    http: Arc<Http>,
+    /// Where the track was requested, used unless the guild set an announce channel.
+    channel: ChannelId,
+    guild_id: GuildId,
+    db_cfg: Arc<DatabaseConfiguration>,
+    songbird: Arc<Songbird>,
+    manager: Arc<NowPlayingManager>,
+    /// Resuming after a pause isn't worth announcing.
+    announced: Arc<AtomicBool>,
+}
+
+impl NowPlayingAnnouncer {
+    pub async fn new(ctx: &ExecutionContext<'_>, guild_id: GuildId) -> Self {
+        Self {
+            http: Arc::clone(&ctx.ctx.http),
+            channel: ctx.cmd.channel_id,
+            guild_id,
+            db_cfg: db::share_config(ctx.db_cfg),
+            songbird: songbird::get(ctx.ctx).await.expect("songbird initialized"),
+            manager: get(ctx.ctx).await.expect("now playing manager initialized"),
+            announced: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether nothing follows `ended`. The queue may or may not have moved past it yet.
+    async fn queue_ended_with(&self, ended: &TrackHandle) -> bool {
+        let Some(call) = self.songbird.get(self.guild_id) else {
+            return true;
+        };
+        let queue = call.lock().await.queue().clone();
+        match queue.current() {
+            Some(current) if current.uuid() == ended.uuid() => queue.len() <= 1,
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    /// Same place, for another track.
+    pub fn for_next_track(&self) -> Self {
+        Self {
+            announced: Arc::new(AtomicBool::new(false)),
+            ..self.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for NowPlayingAnnouncer {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        let EventContext::Track(track_list) = ctx else {
+            return None;
+        };
+        for (state, handle) in *track_list {
+            if state.playing.is_done() {
+                if self.announced.load(Ordering::Relaxed) && self.queue_ended_with(handle).await {
+                    self.manager.clear(&self.http, self.guild_id).await;
+                }
+                continue;
+            }
+            if !matches!(state.playing, PlayMode::Play) || self.announced.swap(true, Ordering::Relaxed) {
+                continue;
+            }
+            let info = track_info(handle);
+            let guild_settings = match guilds::load_guild_settings(&self.db_cfg, u64::from(self.guild_id).into()).await {
+                Ok(guild_settings) => guild_settings,
+                Err(e) => {
+                    trc::warn!("NOW-PLAYING-SETTINGS-FAIL {} {e:?}", self.guild_id);
+                    continue;
+                },
+            };
+
+            let channel = guild_settings.announce_channel.as_ref()
+                .and_then(|channel| channel.to_u64())
+                .map_or(self.channel, ChannelId::new);
+            self.manager.announce(&self.http, self.guild_id, channel, guild_settings.now_playing_mode(), &info).await;
+        }
+        None
+    }
+}
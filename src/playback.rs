@@ -0,0 +1,40 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use serenity::{all::{Context, GuildId}, prelude::TypeMapKey};
+
+pub struct PlaybackManagerKey;
+
+impl TypeMapKey for PlaybackManagerKey {
+    type Value = Arc<PlaybackManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<PlaybackManager>> {
+    ctx.data.read().await.get::<PlaybackManagerKey>().cloned()
+}
+
+/// Counts how often each guild's playback was stopped, so work outliving a track, like retrying it, can tell whether
+/// the queue it was for is still around.
+#[derive(Default)]
+pub struct PlaybackManager {
+    sessions: Mutex<HashMap<GuildId, u64>>,
+}
+
+impl PlaybackManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The guild's current session, to hold on to and check with [`Self::is_current`] later.
+    pub fn session(&self, guild_id: GuildId) -> u64 {
+        self.sessions.lock().expect("not poisoned").get(&guild_id).copied().unwrap_or_default()
+    }
+
+    pub fn is_current(&self, guild_id: GuildId, session: u64) -> bool {
+        self.session(guild_id) == session
+    }
+
+    /// For when the queue is stopped, replaced or left behind. Call before queueing anything new.
+    pub fn end_session(&self, guild_id: GuildId) {
+        *self.sessions.lock().expect("not poisoned").entry(guild_id).or_default() += 1;
+    }
+}
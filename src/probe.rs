@@ -0,0 +1,78 @@
+use std::{io::Cursor, path::Path};
+
+use songbird::input::codecs::{get_codec_registry, get_probe};
+use symphonia::core::{codecs::{DecoderOptions, CODEC_TYPE_NULL}, errors::Error as SymphoniaError, formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint};
+
+/// How many packets we're willing to look through for one that decodes before calling the file broken.
+const DECODE_ATTEMPTS: usize = 16;
+
+/// What symphonia could tell about an audio file.
+#[derive(Debug, Clone)]
+pub struct AudioProbe {
+    pub codec: String,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub duration_secs: Option<f64>,
+}
+
+/// Checks that `data` is audio songbird can actually play, by probing the container and decoding a packet.
+///
+/// Uses songbird's registries rather than symphonia's defaults, so that anything accepted here (e.g. Opus, which
+/// symphonia can't decode by itself) is playable.
+pub fn probe_audio(filename: &str, data: Vec<u8>) -> Result<AudioProbe, String> {
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(filename).extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let source = MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default());
+    let mut probed = get_probe()
+        .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|_e| "that doesn't look like an audio file".to_owned())?;
+    let track = probed.format.default_track()
+        .filter(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "that file has no audio in it".to_owned())?;
+    let track_id = track.id;
+    let params = track.codec_params.clone();
+
+    let codec = get_codec_registry().get_codec(params.codec)
+        .map(|descriptor| descriptor.short_name.to_owned())
+        .ok_or_else(|| "that audio format isn't supported".to_owned())?;
+    let mut decoder = get_codec_registry().make(&params, &DecoderOptions::default())
+        .map_err(|_e| "that audio format isn't supported".to_owned())?;
+
+    let mut decoded = false;
+    for _ in 0..DECODE_ATTEMPTS {
+        let packet = match probed.format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::ResetRequired) => continue,
+            Err(_) => break,
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        if decoder.decode(&packet).is_ok() {
+            decoded = true;
+            break;
+        }
+    }
+    if !decoded {
+        return Err("that audio file couldn't be decoded, it might be corrupted".to_owned());
+    }
+
+    let duration_secs = match (params.time_base, params.n_frames) {
+        (Some(time_base), Some(n_frames)) => {
+            let time = time_base.calc_time(n_frames);
+            Some(time.seconds as f64 + time.frac)
+        },
+        (None, Some(n_frames)) => params.sample_rate.map(|rate| n_frames as f64 / rate as f64),
+        _ => None,
+    };
+
+    Ok(AudioProbe {
+        codec,
+        sample_rate: params.sample_rate,
+        channels: params.channels.map(|channels| channels.count() as u16),
+        duration_secs,
+    })
+}
@@ -0,0 +1,279 @@
+use std::{collections::HashMap, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
+
+use azel::DatabaseConfiguration;
+use serenity::{all::{ChannelId, Context, GuildId, UserId}, prelude::TypeMapKey};
+use songbird::{driver::DecodeMode, events::context_data::VoiceTick, Call, Event, EventContext, EventHandler};
+use tracing as trc;
+
+use crate::{async_trait, db::recording};
+
+/// Matches the decode settings songbird is configured with in `main`.
+pub const SAMPLE_RATE: u32 = 16_000;
+/// Voice ticks come every 20ms.
+pub const TICK_SAMPLES: usize = SAMPLE_RATE as usize / 50;
+/// Largest attachment Discord takes from bots in servers without boosts.
+const MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+/// Longest recording whose [`encode_wav`] output still fits in [`MAX_UPLOAD_BYTES`].
+pub const MAX_POSTABLE_SECS: u64 = (MAX_UPLOAD_BYTES - WAV_HEADER_BYTES as u64) / (SAMPLE_RATE as u64 * 2);
+const WAV_HEADER_BYTES: usize = 44;
+
+pub struct RecordingManagerKey;
+
+impl TypeMapKey for RecordingManagerKey {
+    type Value = Arc<RecordingManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<RecordingManager>> {
+    ctx.data.read().await.get::<RecordingManagerKey>().cloned()
+}
+
+/// Has the call decode voice, or stop doing so. Only recordings and clips need decoded voice, so it's left off
+/// otherwise.
+pub fn set_decoding(call: &mut Call, decode: bool) {
+    let mode = if decode { DecodeMode::Decode } else { DecodeMode::Pass };
+    if call.config().decode_mode != mode {
+        let config = call.config().clone().decode_mode(mode);
+        call.set_config(config);
+    }
+}
+
+/// Recordings in progress, at most one per guild. They only live in memory, a restart loses them.
+#[derive(Default)]
+pub struct RecordingManager {
+    active: Mutex<HashMap<GuildId, Arc<Recording>>>,
+}
+
+impl RecordingManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns false if the guild is already being recorded.
+    pub fn begin(&self, guild_id: GuildId, recording: Arc<Recording>) -> bool {
+        let mut active = self.active.lock().expect("not poisoned");
+        if active.contains_key(&guild_id) {
+            return false;
+        }
+        active.insert(guild_id, recording);
+        true
+    }
+
+    /// Whether the guild's voice is being captured right now, as opposed to a stopped recording waiting to be posted.
+    pub fn is_recording(&self, guild_id: GuildId) -> bool {
+        self.active.lock().expect("not poisoned").get(&guild_id).is_some_and(|recording| !recording.stopped.load(Ordering::Relaxed))
+    }
+
+    /// The guild's recording, whether it's still capturing or stopped and waiting to be posted.
+    pub fn current(&self, guild_id: GuildId) -> Option<Arc<Recording>> {
+        self.active.lock().expect("not poisoned").get(&guild_id).cloned()
+    }
+
+    /// Stops capturing, handing back what was recorded so far. The recording stays around until [`Self::finish`],
+    /// so it isn't lost if posting it fails.
+    pub fn stop(&self, guild_id: GuildId) -> Option<Arc<Recording>> {
+        let recording = self.active.lock().expect("not poisoned").get(&guild_id).cloned()?;
+        recording.stopped.store(true, Ordering::Relaxed);
+        Some(recording)
+    }
+
+    /// Forgets the guild's stopped recording, once it's been dealt with.
+    pub fn finish(&self, guild_id: GuildId) {
+        self.active.lock().expect("not poisoned").remove(&guild_id);
+    }
+}
+
+/// Who is behind each SSRC in a call, leaving out anyone who opted out of being recorded.
+pub struct Speakers {
+    db_cfg: Arc<DatabaseConfiguration>,
+    /// `None` for those who opted out.
+    by_ssrc: Mutex<HashMap<u32, Option<UserId>>>,
+}
+
+impl Speakers {
+    pub fn new(db_cfg: Arc<DatabaseConfiguration>) -> Self {
+        Self {
+            db_cfg,
+            by_ssrc: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The user to record for `ssrc`, if known and not opted out.
+    pub fn recordable(&self, ssrc: u32) -> Option<UserId> {
+        self.by_ssrc.lock().expect("not poisoned").get(&ssrc).copied().flatten()
+    }
+
+    pub async fn identify(&self, ssrc: u32, user_id: UserId) {
+        if self.by_ssrc.lock().expect("not poisoned").contains_key(&ssrc) {
+            return;
+        }
+        let opted_out = match recording::load_opted_out(&self.db_cfg, &[u64::from(user_id).into()]).await {
+            Ok(opted_out) => !opted_out.is_empty(),
+            Err(e) => {
+                // Leave them out if we can't tell.
+                trc::warn!("RECORD-OPT-OUT-CHECK-FAIL {user_id} {e:?}");
+                true
+            },
+        };
+        self.by_ssrc.lock().expect("not poisoned").insert(ssrc, (!opted_out).then_some(user_id));
+    }
+}
+
+/// Decoded voice from one channel, kept per speaker so anyone opting out midway can still be left out.
+pub struct Recording {
+    pub channel: ChannelId,
+    speakers: Speakers,
+    max_ticks: usize,
+    stopped: AtomicBool,
+    state: Mutex<RecordingState>,
+    /// Sound name this was saved as and the line saying how to play it, so a retried post doesn't save it twice.
+    saved: Mutex<Option<(String, String)>>,
+}
+
+#[derive(Default)]
+struct RecordingState {
+    ticks: usize,
+    /// Each speaker's audio, lined up so sample `n` of everyone happened at the same time.
+    voices: HashMap<UserId, Vec<i16>>,
+}
+
+impl Recording {
+    /// Recordings longer than [`MAX_POSTABLE_SECS`] couldn't be posted, so `max_duration_secs` gets capped to that.
+    pub fn new(channel: ChannelId, db_cfg: Arc<DatabaseConfiguration>, max_duration_secs: u64) -> Self {
+        Self {
+            channel,
+            speakers: Speakers::new(db_cfg),
+            max_ticks: usize::try_from(max_duration_secs.min(MAX_POSTABLE_SECS) * 50).unwrap_or(usize::MAX),
+            stopped: AtomicBool::new(false),
+            state: Mutex::new(RecordingState::default()),
+            saved: Mutex::new(None),
+        }
+    }
+
+    pub fn duration_secs(&self) -> u64 {
+        (self.state.lock().expect("not poisoned").ticks / 50) as u64
+    }
+
+    /// A copy of everyone's audio. The recording keeps its own until it's dropped.
+    pub fn voices(&self) -> HashMap<UserId, Vec<i16>> {
+        self.state.lock().expect("not poisoned").voices.clone()
+    }
+
+    /// How to play the sound this was saved as, if it was already saved as `name`.
+    pub fn saved_as(&self, name: &str) -> Option<String> {
+        self.saved.lock().expect("not poisoned").as_ref().filter(|(saved, _)| saved == name).map(|(_, line)| line.clone())
+    }
+
+    pub fn mark_saved(&self, name: &str, line: String) {
+        *self.saved.lock().expect("not poisoned") = Some((name.to_owned(), line));
+    }
+
+    fn capture(&self, tick: &VoiceTick) {
+        let mut state = self.state.lock().expect("not poisoned");
+        if state.ticks >= self.max_ticks {
+            return;
+        }
+
+        let start = state.ticks * TICK_SAMPLES;
+        for (ssrc, data) in tick.speaking.iter() {
+            let (Some(user_id), Some(decoded)) = (self.speakers.recordable(*ssrc), data.decoded_voice.as_ref()) else {
+                continue;
+            };
+            let voice = state.voices.entry(user_id).or_default();
+            voice.resize(start, 0);
+            voice.extend(decoded.iter().take(TICK_SAMPLES));
+        }
+        state.ticks += 1;
+    }
+}
+
+/// Feeds a [`Recording`]. Register as a global handler for both `VoiceTick` and `SpeakingStateUpdate`, it removes
+/// itself once the recording ends.
+#[derive(Clone)]
+pub struct RecordingReceiver(pub Arc<Recording>);
+
+#[async_trait]
+impl EventHandler for RecordingReceiver {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if self.0.stopped.load(Ordering::Relaxed) {
+            return Some(Event::Cancel);
+        }
+        match ctx {
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    self.0.speakers.identify(speaking.ssrc, UserId::new(user_id.0)).await;
+                }
+            },
+            EventContext::VoiceTick(tick) => self.0.capture(tick),
+            _ => {},
+        }
+        None
+    }
+}
+
+/// Sums everyone's audio into one track, clipping rather than wrapping around.
+pub fn mix<'a>(voices: impl Iterator<Item = &'a Vec<i16>>) -> Vec<i16> {
+    let mut mixed: Vec<i32> = vec![];
+    for voice in voices {
+        if mixed.len() < voice.len() {
+            mixed.resize(voice.len(), 0);
+        }
+        for (total, sample) in mixed.iter_mut().zip(voice.iter()) {
+            *total += i32::from(*sample);
+        }
+    }
+    mixed.into_iter().map(|total| total.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16).collect()
+}
+
+/// 16-bit mono PCM at [`SAMPLE_RATE`].
+pub fn encode_wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(WAV_HEADER_BYTES + samples.len() * 2);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, mono.
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_sums_and_clips() {
+        let voices = [vec![1, i16::MAX, i16::MIN], vec![2, 1, -1, 7]];
+        assert_eq!(mix(voices.iter()), vec![3, i16::MAX, i16::MIN, 7]);
+        assert!(mix(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn encode_wav_writes_a_pcm_header() {
+        let wav = encode_wav(&[0, 1, -1]);
+        assert_eq!(wav.len(), WAV_HEADER_BYTES + 6);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 6);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), SAMPLE_RATE);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 6);
+        assert_eq!(&wav[44..], &[0, 0, 1, 0, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn longest_recording_fits_the_upload_limit() {
+        let samples = MAX_POSTABLE_SECS as usize * SAMPLE_RATE as usize;
+        assert!((WAV_HEADER_BYTES + samples * 2) as u64 <= MAX_UPLOAD_BYTES);
+    }
+}
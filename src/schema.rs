@@ -1,5 +1,25 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audio_blobs (hash) {
+        #[max_length = 64]
+        hash -> Varchar,
+        #[max_length = 1024]
+        directory -> Varchar,
+        ref_count -> Int8,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    audio_tags (id) {
+        id -> Int8,
+        audio -> Int8,
+        #[max_length = 32]
+        tag -> Varchar,
+    }
+}
+
 diesel::table! {
     audio_ledger (id) {
         id -> Int8,
@@ -9,14 +29,88 @@ diesel::table! {
         #[max_length = 1024]
         file_path -> Varchar,
         uploader -> Numeric,
+        #[max_length = 32]
+        codec -> Nullable<Varchar>,
+        sample_rate -> Nullable<Int4>,
+        channels -> Nullable<Int2>,
+        duration_secs -> Nullable<Float8>,
+        #[max_length = 1024]
+        original_file_path -> Nullable<Varchar>,
+        #[max_length = 64]
+        blob_hash -> Nullable<Varchar>,
+        guild_id -> Nullable<Numeric>,
+        #[max_length = 16]
+        visibility -> Varchar,
+        play_count -> Int8,
+    }
+}
+
+diesel::table! {
+    download_cache (video_id) {
+        #[max_length = 64]
+        video_id -> Varchar,
+        #[max_length = 1024]
+        file_path -> Varchar,
+        size_bytes -> Int8,
+        last_played -> Timestamptz,
+        pinned -> Bool,
+    }
+}
+
+diesel::table! {
+    guild_settings (guild_id) {
+        guild_id -> Numeric,
+        clips_enabled -> Bool,
+        ducking_enabled -> Bool,
+        ducking_volume -> Int2,
+        #[max_length = 16]
+        now_playing_mode -> Varchar,
+        announce_channel -> Nullable<Numeric>,
+    }
+}
+
+diesel::table! {
+    media_metadata (id) {
+        id -> Int8,
+        #[max_length = 64]
+        extractor -> Varchar,
+        #[max_length = 64]
+        video_id -> Varchar,
+        #[max_length = 1024]
+        url -> Varchar,
+        #[max_length = 1024]
+        title -> Varchar,
+        #[max_length = 256]
+        uploader -> Nullable<Varchar>,
+        duration_secs -> Nullable<Float8>,
+        #[max_length = 2048]
+        thumbnail_url -> Nullable<Varchar>,
+        fetched_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    play_events (id) {
+        id -> Int8,
+        guild_id -> Numeric,
+        user_id -> Numeric,
+        #[max_length = 1024]
+        source -> Varchar,
+        #[max_length = 1024]
+        title -> Varchar,
+        #[max_length = 16]
+        kind -> Varchar,
+        seconds_listened -> Float8,
+        occurred_at -> Timestamptz,
     }
 }
 
 diesel::table! {
     playlist_entries (id) {
         id -> Int8,
-        playlist -> Nullable<Int8>,
-        audio -> Nullable<Int8>,
+        playlist -> Int8,
+        audio -> Int8,
+        position -> Int4,
     }
 }
 
@@ -29,11 +123,57 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    recording_opt_outs (discord_user) {
+        discord_user -> Numeric,
+        opted_out_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    speaking_time (guild_id, discord_user, day) {
+        guild_id -> Numeric,
+        discord_user -> Numeric,
+        day -> Date,
+        seconds_spoken -> Float8,
+    }
+}
+
+diesel::table! {
+    track_ratings (id) {
+        id -> Int8,
+        discord_user -> Numeric,
+        audio -> Int8,
+        rating -> Int2,
+        rated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    voice_stats_opt_ins (discord_user) {
+        discord_user -> Numeric,
+        opted_in_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(audio_ledger -> audio_blobs (blob_hash));
+diesel::joinable!(audio_tags -> audio_ledger (audio));
 diesel::joinable!(playlist_entries -> audio_ledger (audio));
 diesel::joinable!(playlist_entries -> playlists (playlist));
+diesel::joinable!(track_ratings -> audio_ledger (audio));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audio_blobs,
     audio_ledger,
+    audio_tags,
+    download_cache,
+    guild_settings,
+    media_metadata,
+    play_events,
     playlist_entries,
     playlists,
+    recording_opt_outs,
+    speaking_time,
+    track_ratings,
+    voice_stats_opt_ins,
 );
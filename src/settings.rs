@@ -0,0 +1,227 @@
+use std::{path::PathBuf, sync::OnceLock};
+
+use serde::Deserialize;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Yamble specific configuration, read from `yamble.toml` (optional) and `YAMBLE__*` environment variables.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Discord user ids allowed to run `/admin` commands.
+    pub owners: Vec<u64>,
+    pub cache: CacheSettings,
+    pub limits: LimitSettings,
+    pub ytdlp: YtdlpSettings,
+    pub downloads: DownloadSettings,
+    pub uploads: UploadSettings,
+    pub transcode: TranscodeSettings,
+    pub stats: StatsSettings,
+    pub recording: RecordingSettings,
+    pub clips: ClipSettings,
+    pub ducking: DuckingSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheSettings {
+    pub directory: PathBuf,
+    /// Once the cache grows past this, least recently played downloads get evicted.
+    pub budget_bytes: u64,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("downloads/yt"),
+            budget_bytes: 10 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LimitSettings {
+    /// Longest video or upload accepted, in seconds. 0 disables the check.
+    pub max_duration_secs: u64,
+    /// Largest download or upload accepted, in bytes. 0 disables the check.
+    pub max_filesize_bytes: u64,
+    /// Per-guild overrides. A guild can only tighten the global limits, never loosen them.
+    pub guilds: Vec<GuildLimitSettings>,
+}
+
+impl Default for LimitSettings {
+    fn default() -> Self {
+        Self {
+            max_duration_secs: 60 * 60,
+            max_filesize_bytes: 200 * 1024 * 1024,
+            guilds: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GuildLimitSettings {
+    pub guild: u64,
+    pub max_duration_secs: Option<u64>,
+    pub max_filesize_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct YtdlpSettings {
+    pub path: String,
+    /// Release tag fetched from github, e.g. `2024.12.13`.
+    pub version: String,
+    /// Expected sha256 of the binary. Falls back to the checksums published with the release when unset.
+    pub sha256: Option<String>,
+}
+
+impl Default for YtdlpSettings {
+    fn default() -> Self {
+        Self {
+            path: "resources/bin/ytdlp/yt-dlp".to_owned(),
+            version: "2024.12.13".to_owned(),
+            sha256: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DownloadSettings {
+    /// How many yt-dlp downloads may run at once. Anything past this waits its turn.
+    pub workers: usize,
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+        }
+    }
+}
+
+/// Caps specific to `/upload`, applied on top of [`LimitSettings`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UploadSettings {
+    /// 0 disables the check.
+    pub max_duration_secs: u64,
+    /// 0 disables the check.
+    pub max_filesize_bytes: u64,
+}
+
+impl Default for UploadSettings {
+    fn default() -> Self {
+        Self {
+            max_duration_secs: 10 * 60,
+            max_filesize_bytes: 25 * 1024 * 1024,
+        }
+    }
+}
+
+/// Uploads get converted to Ogg/Opus at 48kHz, which is what discord wants anyways.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TranscodeSettings {
+    pub enabled: bool,
+    pub ffmpeg_path: String,
+    pub bitrate_kbps: u32,
+    /// Keep the file as uploaded next to the transcoded one.
+    pub keep_original: bool,
+}
+
+impl Default for TranscodeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ffmpeg_path: "ffmpeg".to_owned(),
+            bitrate_kbps: 96,
+            keep_original: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StatsSettings {
+    /// IANA time zone that `/stats` buckets plays in, unless one is given.
+    pub timezone: String,
+}
+
+impl Default for StatsSettings {
+    fn default() -> Self {
+        Self {
+            timezone: "UTC".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RecordingSettings {
+    /// `/record` stops capturing after this long, in seconds. Recordings are kept in memory until stopped.
+    pub max_duration_secs: u64,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            max_duration_secs: 10 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ClipSettings {
+    /// How much voice guilds with clips enabled keep around, in seconds. This is the longest `/clip` can be.
+    pub buffer_secs: u64,
+    /// What `/clip` saves when not told how much.
+    pub default_secs: u64,
+}
+
+impl Default for ClipSettings {
+    fn default() -> Self {
+        Self {
+            buffer_secs: 60,
+            default_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DuckingSettings {
+    /// How long the music takes to go from full volume all the way down, in milliseconds.
+    pub attack_ms: u64,
+    /// How long it takes to come back up once everyone stopped talking, in milliseconds.
+    pub release_ms: u64,
+    /// How long to wait after the last voice before coming back up, so pauses between words don't pump the volume.
+    pub hold_ms: u64,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            attack_ms: 150,
+            release_ms: 1000,
+            hold_ms: 500,
+        }
+    }
+}
+
+pub fn load() -> Result<&'static Settings, config::ConfigError> {
+    let settings = config::Config::builder()
+        .add_source(config::File::with_name("yamble").required(false))
+        .add_source(config::Environment::with_prefix("YAMBLE").prefix_separator("__").separator("__"))
+        .build()?
+        .try_deserialize()?;
+
+    Ok(SETTINGS.get_or_init(|| settings))
+}
+
+pub fn get() -> &'static Settings {
+    SETTINGS.get().expect("settings loaded")
+}
@@ -0,0 +1,174 @@
+use std::{fs::File, io::Write, path::{Path, PathBuf}};
+
+use azel::{cmd::RequestError, DatabaseConfiguration};
+use bigdecimal::BigDecimal;
+use sha2::{Digest, Sha256};
+use tracing as trc;
+
+use crate::{db::{blobs::{self, NewAudioBlob}, NewAudioLedgerEntry, Visibility}, probe::AudioProbe, transcode::{self, Canonicalized}};
+
+/// Where `/upload` stores sounds, as `uploaded/blobs/<sha256>/<filename>`.
+const BLOB_DIR: &str = "uploaded/blobs";
+
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+pub fn blob_dir(hash: &str) -> PathBuf {
+    Path::new(BLOB_DIR).join(hash)
+}
+
+/// Where [`store_sound`] put a new sound.
+#[derive(Debug)]
+pub enum StoredSound {
+    /// Same audio as an existing sound, called this, so only the name was added.
+    Duplicate(String),
+    /// Freshly stored, as described by the probe.
+    New(AudioProbe),
+}
+
+/// A new sound to add to the library.
+#[derive(Debug)]
+pub struct NewSound<'a> {
+    pub name: &'a str,
+    /// Suggested by whoever provided the audio, only the file name part is used.
+    pub filename: &'a str,
+    pub data: &'a [u8],
+    pub probe: AudioProbe,
+    pub uploader: BigDecimal,
+    pub guild_id: Option<BigDecimal>,
+}
+
+/// Stores already checked audio as a sound, deduplicated by content and transcoded to the canonical format.
+pub async fn store_sound(cfg: &DatabaseConfiguration, sound: NewSound<'_>) -> Result<StoredSound, RequestError> {
+    // Sounds start out shared with the guild they're uploaded in, see `/sound share`.
+    let visibility = if sound.guild_id.is_some() { Visibility::Guild } else { Visibility::Private };
+
+    let hash = content_hash(sound.data);
+    let directory = blob_dir(hash.as_str());
+    let blob = NewAudioBlob {
+        hash: hash.as_str(),
+        directory: &directory.to_string_lossy(),
+    };
+
+    // Same audio as an existing sound, so just add another name for it. Unless that sound is removed before the new
+    // name makes it in, then it's stored anew below.
+    if let Some(existing) = blobs::load_blob_reference(cfg, hash.as_str()).await? {
+        let added = blobs::add_audio_reference(cfg, hash.as_str(), &NewAudioLedgerEntry {
+            link_or_name: sound.name,
+            downloaded: true,
+            file_path: existing.file_path,
+            uploader: sound.uploader.clone(),
+            codec: existing.codec.as_deref(),
+            sample_rate: existing.sample_rate,
+            channels: existing.channels,
+            duration_secs: existing.duration_secs,
+            original_file_path: existing.original_file_path,
+            blob_hash: Some(hash.as_str()),
+            guild_id: sound.guild_id.clone(),
+            visibility: visibility.as_str(),
+        }).await?;
+        if added {
+            trc::info!("UPLOAD-DEDUP {:?} {:?}", sound.name, hash);
+            return Ok(StoredSound::Duplicate(existing.link_or_name));
+        }
+    }
+
+    let (download_path, mut download_output) = generate_filepath(directory.as_path(), sound.filename)?;
+    let written = download_output.write_all(sound.data);
+    drop(download_output);
+    if written.is_err() {
+        remove_upload(download_path.as_str());
+        return Err(RequestError::Internal("Could not download file.".into()));
+    }
+
+    let stored = match transcode::canonicalize(Path::new(download_path.as_str()), sound.probe.clone()).await {
+        Ok(canonical) => canonical,
+        Err(reason) => {
+            // Still playable as is, just not as cheaply.
+            trc::warn!("UPLOAD-TRANSCODE-FAIL {:?} {reason}", download_path);
+            Canonicalized::unchanged(download_path.clone().into(), sound.probe)
+        },
+    };
+    let probe = &stored.probe;
+
+    let new_data = NewAudioLedgerEntry {
+        link_or_name: sound.name,
+        downloaded: true,
+        file_path: stored.file_path.to_string_lossy().into_owned(),
+        uploader: sound.uploader,
+        codec: Some(probe.codec.as_str()),
+        sample_rate: probe.sample_rate.and_then(|rate| i32::try_from(rate).ok()),
+        channels: probe.channels.and_then(|channels| i16::try_from(channels).ok()),
+        duration_secs: probe.duration_secs,
+        original_file_path: stored.kept_original().map(|path| path.to_string_lossy().into_owned()),
+        blob_hash: Some(hash.as_str()),
+        guild_id: sound.guild_id,
+        visibility: visibility.as_str(),
+    };
+
+    // The file and its ledger entry only make sense together, so undo the write if the insert fails. Unless the same
+    // audio was stored by someone else meanwhile, then the files are theirs too.
+    if let Err(e) = blobs::track_audio_reference(cfg, &blob, &new_data).await {
+        if !blobs::is_blob_referenced(cfg, hash.as_str()).await.unwrap_or(true) {
+            stored.abort();
+            remove_upload(download_path.as_str());
+        }
+        return Err(e);
+    }
+    let probe = stored.probe.clone();
+    stored.commit();
+
+    Ok(StoredSound::New(probe))
+}
+
+fn generate_filepath(download_dir: &Path, filename: &str) -> Result<(String, File), RequestError> {
+    // Attachment names come from the user, so don't let them pick the directory.
+    let filename = Path::new(filename).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "sound".to_owned());
+    std::fs::create_dir_all(download_dir).map_err(|e| RequestError::Internal(format!("upload dir create failed {e:?}").into()))?;
+
+    let download_path = download_dir.join(filename).to_string_lossy().into_owned();
+    let f = File::create(download_path.as_str()).map_err(|e| RequestError::Internal(format!("upload file create failed {e:?}").into()))?;
+
+    Ok((download_path, f))
+}
+
+fn remove_upload(download_path: &str) {
+    let path = Path::new(download_path);
+    if let Err(e) = std::fs::remove_file(path) {
+        trc::warn!("UPLOAD-CLEANUP-FAIL {:?} {e:?}", path);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::remove_dir(dir).ok();
+    }
+}
+
+/// Deletes a sound from the ledger. Its audio files only go once no other name refers to them.
+pub async fn remove_sound(cfg: &DatabaseConfiguration, id: i64) -> Result<bool, RequestError> {
+    let Some((entry, blob)) = blobs::release_audio_reference(cfg, id).await? else {
+        return Ok(false);
+    };
+
+    match (entry.blob_hash.as_ref(), blob) {
+        (Some(_), Some(blob)) => {
+            trc::info!("BLOB-REMOVE {:?}", blob.hash);
+            if let Err(e) = std::fs::remove_dir_all(blob.directory.as_str()) {
+                trc::warn!("BLOB-REMOVE-FAIL {:?} {e:?}", blob.directory);
+            }
+        },
+        (Some(_), None) => {},
+        // Recorded before blobs existed, so the entry owns its files.
+        (None, _) => {
+            for path in std::iter::once(entry.file_path.as_str()).chain(entry.original_file_path.as_deref()) {
+                if let Err(e) = std::fs::remove_file(path) {
+                    trc::warn!("SOUND-REMOVE-FAIL {:?} {e:?}", path);
+                }
+            }
+            if let Some(dir) = Path::new(entry.file_path.as_str()).parent() {
+                std::fs::remove_dir(dir).ok();
+            }
+        },
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,127 @@
+use std::{path::{Path, PathBuf}, process::Stdio};
+
+use tracing as trc;
+
+use crate::{probe::{self, AudioProbe}, settings::{self, TranscodeSettings}};
+
+pub const CANONICAL_CODEC: &str = "opus";
+pub const CANONICAL_SAMPLE_RATE: u32 = 48_000;
+const CANONICAL_FILENAME: &str = "audio.opus.ogg";
+
+/// Where a sound's audio ended up after [`canonicalize`].
+#[derive(Debug)]
+pub struct Canonicalized {
+    pub file_path: PathBuf,
+    /// Set if a transcoded copy was made. It stays on disk until [`Canonicalized::commit`].
+    pub original: Option<PathBuf>,
+    pub probe: AudioProbe,
+}
+
+impl Canonicalized {
+    /// Untouched file, e.g. as a fallback when transcoding fails.
+    pub fn unchanged(file_path: PathBuf, probe: AudioProbe) -> Self {
+        Self {
+            file_path,
+            original: None,
+            probe,
+        }
+    }
+
+    /// The original, if it's configured to outlive the transcode. This is what should get recorded.
+    pub fn kept_original(&self) -> Option<&Path> {
+        self.original.as_deref().filter(|_| settings::get().transcode.keep_original)
+    }
+
+    /// Call once the new file is recorded. Deletes the original unless it's configured to be kept.
+    pub fn commit(self) {
+        if self.kept_original().is_some() {
+            return;
+        }
+        if let Some(original) = self.original {
+            if let Err(e) = std::fs::remove_file(original.as_path()) {
+                trc::warn!("TRANSCODE-ORIGINAL-REMOVE-FAIL {:?} {e:?}", original);
+            }
+        }
+    }
+
+    /// Call if recording the new file failed. Deletes the transcoded copy, leaving the original as it was.
+    pub fn abort(self) {
+        if self.original.is_some() {
+            std::fs::remove_file(self.file_path.as_path()).ok();
+        }
+    }
+}
+
+pub fn is_canonical(codec: Option<&str>, sample_rate: Option<u32>) -> bool {
+    codec == Some(CANONICAL_CODEC) && sample_rate == Some(CANONICAL_SAMPLE_RATE)
+}
+
+/// Transcodes `original` to Ogg/Opus at 48kHz, next to it in the same directory.
+///
+/// Opus at 48kHz is what discord sends, so songbird can pass those packets straight through instead of
+/// decoding and re-encoding them on every play. Files already in that format are left as is.
+pub async fn canonicalize(original: &Path, probe: AudioProbe) -> Result<Canonicalized, String> {
+    let settings = &settings::get().transcode;
+    if !settings.enabled || is_canonical(Some(probe.codec.as_str()), probe.sample_rate) {
+        return Ok(Canonicalized::unchanged(original.to_owned(), probe));
+    }
+
+    let target = original.with_file_name(CANONICAL_FILENAME);
+    if target == original {
+        return Err("can't transcode a file onto itself".to_owned());
+    }
+    if let Err(e) = run_ffmpeg(settings, original, target.as_path()).await {
+        std::fs::remove_file(target.as_path()).ok();
+        return Err(e);
+    }
+
+    let probed = match std::fs::read(target.as_path()) {
+        Ok(data) => {
+            let filename = target.to_string_lossy().into_owned();
+            tokio::task::spawn_blocking(move || probe::probe_audio(filename.as_str(), data)).await
+                .map_err(|e| format!("probe failed ({e})"))
+                .and_then(|probed| probed)
+        },
+        Err(e) => Err(format!("transcoded file unreadable ({e})")),
+    };
+    let probe = match probed {
+        Ok(probe) => probe,
+        Err(e) => {
+            std::fs::remove_file(target.as_path()).ok();
+            return Err(e);
+        },
+    };
+
+    Ok(Canonicalized {
+        file_path: target,
+        original: Some(original.to_owned()),
+        probe,
+    })
+}
+
+async fn run_ffmpeg(settings: &TranscodeSettings, input: &Path, output: &Path) -> Result<(), String> {
+    trc::info!("TRANSCODE-START {:?}", input);
+    let result = tokio::process::Command::new(settings.ffmpeg_path.as_str())
+        .args(["-hide_banner", "-loglevel", "error", "-nostdin", "-y", "-i"])
+        .arg(input)
+        .args(["-vn", "-map_metadata", "-1", "-c:a", "libopus", "-application", "audio", "-frame_duration", "20"])
+        .arg("-b:a").arg(format!("{}k", settings.bitrate_kbps))
+        .arg("-ar").arg(CANONICAL_SAMPLE_RATE.to_string())
+        .args(["-ac", "2", "-f", "ogg"])
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("couldn't start ffmpeg ({e})"))?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(result.stderr.as_slice());
+        trc::error!("TRANSCODE-FAIL {:?} {}", input, stderr.trim());
+        return Err(format!("ffmpeg exited with {}", result.status));
+    }
+
+    trc::info!("TRANSCODE-END {:?}", output);
+    Ok(())
+}
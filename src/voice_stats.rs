@@ -0,0 +1,167 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use azel::DatabaseConfiguration;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use chrono_tz::Tz;
+use serenity::{all::{Context, GuildId, UserId}, prelude::TypeMapKey};
+use songbird::{events::context_data::VoiceTick, Call, CoreEvent, Event, EventContext, EventHandler};
+use tracing as trc;
+
+use crate::{async_trait, db::voice_stats::{self, NewSpeakingTime}, settings};
+
+/// Voice ticks come every 20ms.
+const TICK_SECS: f64 = 0.02;
+/// Totals get written about once a minute, and when disconnecting.
+const FLUSH_TICKS: u64 = 50 * 60;
+
+pub struct VoiceStatsManagerKey;
+
+impl TypeMapKey for VoiceStatsManagerKey {
+    type Value = Arc<VoiceStatsManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<VoiceStatsManager>> {
+    ctx.data.read().await.get::<VoiceStatsManagerKey>().cloned()
+}
+
+/// Counts how long people speak in calls. Only who is speaking is looked at, never what is said, and only the totals
+/// of those who opted in ever get stored.
+#[derive(Default)]
+pub struct VoiceStatsManager {
+    trackers: Mutex<HashMap<GuildId, Arc<SpeakingTracker>>>,
+}
+
+impl VoiceStatsManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts counting in the call, unless that's already happening.
+    pub fn listen(&self, guild_id: GuildId, call: &mut Call, db_cfg: Arc<DatabaseConfiguration>) {
+        let mut trackers = self.trackers.lock().expect("not poisoned");
+        // Receivers keep their tracker alive, so only the map holding it means the call it was on is gone.
+        if trackers.get(&guild_id).is_some_and(|tracker| Arc::strong_count(tracker) > 1) {
+            return;
+        }
+
+        let tracker = Arc::new(SpeakingTracker::new(guild_id, db_cfg));
+        let receiver = SpeakingReceiver(Arc::clone(&tracker));
+        call.add_global_event(CoreEvent::SpeakingStateUpdate.into(), receiver.clone());
+        call.add_global_event(CoreEvent::VoiceTick.into(), receiver.clone());
+        call.add_global_event(CoreEvent::DriverDisconnect.into(), receiver);
+        trackers.insert(guild_id, tracker);
+    }
+}
+
+struct SpeakingTracker {
+    guild_id: GuildId,
+    db_cfg: Arc<DatabaseConfiguration>,
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    users_by_ssrc: HashMap<u32, UserId>,
+    /// Ticks spoken by each user since the last flush.
+    ticks: HashMap<UserId, u64>,
+    ticks_since_flush: u64,
+}
+
+impl SpeakingTracker {
+    fn new(guild_id: GuildId, db_cfg: Arc<DatabaseConfiguration>) -> Self {
+        Self {
+            guild_id,
+            db_cfg,
+            state: Mutex::new(TrackerState::default()),
+        }
+    }
+
+    /// Counts everyone who sent voice this tick, handing back the totals once it's time to write them. Only goes by
+    /// who sent packets, so this works without decoding anything.
+    fn count(&self, tick: &VoiceTick) -> Option<HashMap<UserId, u64>> {
+        let mut state = self.state.lock().expect("not poisoned");
+        let sent = tick.speaking.iter().filter(|(_, data)| data.packet.is_some()).map(|(ssrc, _)| ssrc);
+        for ssrc in sent {
+            if let Some(user_id) = state.users_by_ssrc.get(ssrc).copied() {
+                *state.ticks.entry(user_id).or_default() += 1;
+            }
+        }
+        state.ticks_since_flush += 1;
+        if state.ticks_since_flush < FLUSH_TICKS {
+            return None;
+        }
+        state.ticks_since_flush = 0;
+        Some(std::mem::take(&mut state.ticks))
+    }
+
+    fn take(&self) -> HashMap<UserId, u64> {
+        let mut state = self.state.lock().expect("not poisoned");
+        state.ticks_since_flush = 0;
+        std::mem::take(&mut state.ticks)
+    }
+
+    /// Writes in the background, so slow queries don't hold up voice events.
+    fn flush(&self, ticks: HashMap<UserId, u64>) {
+        if ticks.is_empty() {
+            return;
+        }
+        tokio::spawn(save_speaking_time(Arc::clone(&self.db_cfg), self.guild_id, ticks));
+    }
+}
+
+/// Keeps only those opted in at the time of writing, so opting in or out applies right away even mid-call.
+async fn save_speaking_time(db_cfg: Arc<DatabaseConfiguration>, guild_id: GuildId, ticks: HashMap<UserId, u64>) {
+    let user_ids: Vec<BigDecimal> = ticks.keys().map(|user_id| u64::from(*user_id).into()).collect();
+    let opted_in = match voice_stats::load_opted_in(&db_cfg, &user_ids).await {
+        Ok(opted_in) => opted_in,
+        Err(e) => {
+            trc::warn!("VOICE-STATS-OPT-IN-CHECK-FAIL {guild_id} {e:?}");
+            return;
+        },
+    };
+
+    // Same days as `/stats` uses.
+    let timezone: Tz = settings::get().stats.timezone.parse().unwrap_or(Tz::UTC);
+    let day = Utc::now().with_timezone(&timezone).date_naive();
+    let rows: Vec<_> = ticks.into_iter()
+        .map(|(user_id, ticks)| (BigDecimal::from(u64::from(user_id)), ticks))
+        .filter(|(user_id, _)| opted_in.contains(user_id))
+        .map(|(discord_user, ticks)| NewSpeakingTime {
+            guild_id: u64::from(guild_id).into(),
+            discord_user,
+            day,
+            seconds_spoken: ticks as f64 * TICK_SECS,
+        })
+        .collect();
+    if rows.is_empty() {
+        return;
+    }
+    if let Err(e) = voice_stats::add_speaking_time(&db_cfg, &rows).await {
+        trc::warn!("VOICE-STATS-SAVE-FAIL {guild_id} {e:?}");
+    }
+}
+
+#[derive(Clone)]
+struct SpeakingReceiver(Arc<SpeakingTracker>);
+
+#[async_trait]
+impl EventHandler for SpeakingReceiver {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        match ctx {
+            EventContext::SpeakingStateUpdate(speaking) => {
+                if let Some(user_id) = speaking.user_id {
+                    self.0.state.lock().expect("not poisoned").users_by_ssrc.insert(speaking.ssrc, UserId::new(user_id.0));
+                }
+            },
+            EventContext::VoiceTick(tick) => {
+                if let Some(ticks) = self.0.count(tick) {
+                    self.0.flush(ticks);
+                }
+            },
+            EventContext::DriverDisconnect(_) => self.0.flush(self.0.take()),
+            _ => {},
+        }
+        None
+    }
+}
@@ -0,0 +1,207 @@
+use std::{path::Path, sync::Arc};
+
+use azel::cmd::RequestError;
+use serenity::{all::Context, prelude::TypeMapKey};
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, RwLock};
+use tracing as trc;
+
+use crate::settings::YtdlpSettings;
+
+const RELEASE_BASE_URL: &str = "https://github.com/yt-dlp/yt-dlp/releases";
+const CHECKSUMS_ASSET: &str = "SHA2-256SUMS";
+
+#[cfg(target_os = "windows")]
+const BINARY_ASSET: &str = "yt-dlp.exe";
+#[cfg(target_os = "macos")]
+const BINARY_ASSET: &str = "yt-dlp_macos";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const BINARY_ASSET: &str = "yt-dlp";
+
+pub struct YtdlpManagerKey;
+
+impl TypeMapKey for YtdlpManagerKey {
+    type Value = Arc<YtdlpManager>;
+}
+
+pub async fn get(ctx: &Context) -> Option<Arc<YtdlpManager>> {
+    ctx.data.read().await.get::<YtdlpManagerKey>().cloned()
+}
+
+/// Owns the yt-dlp binary: fetching it, checking it against a known checksum and swapping in updates.
+///
+/// The binary always lives at the configured path. Updates are written next to it and renamed over it, so
+/// anything already running keeps the old binary and everything started afterwards gets the new one.
+#[derive(Debug)]
+pub struct YtdlpManager {
+    path: &'static str,
+    pinned_version: &'static str,
+    pinned_sha256: Option<&'static str>,
+    /// Version reported by the binary, `None` if it's missing or broken.
+    version: RwLock<Option<String>>,
+    update_lock: Mutex<()>,
+}
+
+impl YtdlpManager {
+    pub fn new(settings: &'static YtdlpSettings) -> Self {
+        Self {
+            path: settings.path.as_str(),
+            pinned_version: settings.version.as_str(),
+            pinned_sha256: settings.sha256.as_deref(),
+            version: RwLock::new(None),
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Makes sure the pinned version is in place, fetching it if needed. Meant to run once during startup.
+    pub async fn initialize(&self) -> Result<(), RequestError> {
+        let _guard = self.update_lock.lock().await;
+
+        if let Some(version) = query_version(self.path).await {
+            if version == self.pinned_version {
+                match self.verify_installed(self.pinned_version).await {
+                    Ok(true) => {
+                        trc::info!("YTDLP-LOAD-SKIP {version}");
+                        *self.version.write().await = Some(version);
+                        return Ok(());
+                    },
+                    Ok(false) => {
+                        trc::warn!("YTDLP-LOAD-CHECKSUM-MISMATCH");
+                    },
+                    Err(e) => {
+                        // Most likely github is unreachable, in which case refetching won't work either.
+                        trc::warn!("YTDLP-LOAD-UNVERIFIED {e:?}");
+                        *self.version.write().await = Some(version);
+                        return Ok(());
+                    },
+                }
+            } else {
+                // It works, just isn't the pinned one. Keep using it unless the pinned one installs.
+                trc::info!("YTDLP-LOAD-MISMATCH {version} != {}", self.pinned_version);
+                *self.version.write().await = Some(version);
+            }
+        }
+
+        trc::info!("YTDLP-LOAD-START");
+        if let Err(e) = self.install(Some(self.pinned_version)).await {
+            let Some(version) = self.version.read().await.clone() else {
+                return Err(e);
+            };
+            trc::warn!("YTDLP-LOAD-KEEP-MISMATCHED {version} {e:?}");
+            return Ok(());
+        }
+        trc::info!("YTDLP-LOAD-END");
+
+        Ok(())
+    }
+
+    /// Fetches `version` (or the latest release), verifies it, and atomically replaces the current binary.
+    /// Returns the version now installed.
+    pub async fn update(&self, version: Option<&str>) -> Result<String, RequestError> {
+        let _guard = self.update_lock.lock().await;
+        self.install(version).await
+    }
+
+    pub fn exec_path(&self) -> &'static str {
+        self.path
+    }
+
+    pub async fn version(&self) -> Option<String> {
+        self.version.read().await.clone()
+    }
+
+    /// Exec path, but only if there's a working binary there.
+    pub async fn ready_exec_path(&self) -> Result<&'static str, RequestError> {
+        if self.version.read().await.is_some() {
+            Ok(self.path)
+        } else {
+            Err(RequestError::Internal("yt-dlp isn't available right now, please try again later.".into()))
+        }
+    }
+
+    async fn verify_installed(&self, version: &str) -> Result<bool, RequestError> {
+        let data = std::fs::read(self.path).map_err(|e| RequestError::Internal(format!("ytdlp read failed {e:?}").into()))?;
+        let expected = self.expected_sha256(Some(version)).await?;
+        Ok(sha256_hex(data.as_slice()) == expected)
+    }
+
+    async fn install(&self, version: Option<&str>) -> Result<String, RequestError> {
+        let data = fetch(release_asset_url(version, BINARY_ASSET).as_str()).await?;
+        let expected = self.expected_sha256(version).await?;
+        let actual = sha256_hex(data.as_slice());
+        if actual != expected {
+            trc::error!("YTDLP-CHECKSUM-MISMATCH {actual} != {expected}");
+            return Err(RequestError::Internal("yt-dlp checksum mismatch, refusing to install it.".into()));
+        }
+
+        let path = Path::new(self.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| RequestError::Internal(format!("ytdlp dir create failed {e:?}").into()))?;
+        }
+        let staging_path = path.with_extension(format!("download-{}", uuid::Uuid::new_v4()));
+        let staged = stage_executable(staging_path.as_path(), data.as_slice())
+            .and_then(|_| std::fs::rename(staging_path.as_path(), path));
+        if let Err(e) = staged {
+            std::fs::remove_file(staging_path.as_path()).ok();
+            return Err(RequestError::Internal(format!("ytdlp install failed {e:?}").into()));
+        }
+
+        let installed = query_version(self.path).await;
+        *self.version.write().await = installed.clone();
+        installed.ok_or_else(|| RequestError::Internal("installed yt-dlp doesn't run".into()))
+    }
+
+    async fn expected_sha256(&self, version: Option<&str>) -> Result<String, RequestError> {
+        if let (Some(sha256), Some(version)) = (self.pinned_sha256, version) {
+            if version == self.pinned_version {
+                return Ok(sha256.to_ascii_lowercase());
+            }
+        }
+
+        let sums = fetch(release_asset_url(version, CHECKSUMS_ASSET).as_str()).await?;
+        String::from_utf8_lossy(sums.as_slice())
+            .lines()
+            .filter_map(|line| line.split_once(char::is_whitespace))
+            .find(|(_, name)| name.trim() == BINARY_ASSET)
+            .map(|(sum, _)| sum.to_ascii_lowercase())
+            .ok_or_else(|| RequestError::Internal("yt-dlp release has no checksum for this platform".into()))
+    }
+}
+
+fn release_asset_url(version: Option<&str>, asset: &str) -> String {
+    match version {
+        Some(version) => format!("{RELEASE_BASE_URL}/download/{version}/{asset}"),
+        None => format!("{RELEASE_BASE_URL}/latest/download/{asset}"),
+    }
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>, RequestError> {
+    let response = reqwest::get(url).await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| RequestError::Internal(format!("ytdlp fetch failed {e:?}").into()))?;
+    let data = response.bytes().await
+        .map_err(|e| RequestError::Internal(format!("ytdlp fetch failed {e:?}").into()))?;
+    Ok(data.to_vec())
+}
+
+fn stage_executable(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, data)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+async fn query_version(path: &str) -> Option<String> {
+    let output = tokio::process::Command::new(path).arg("--version").output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(output.stdout.as_slice()).trim().to_owned())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}